@@ -0,0 +1,325 @@
+//!
+//! Helpers that interpret the start and value fields of a Record as Internet resources.
+//!
+
+use crate::{Record, Type};
+
+#[cfg(feature = "ipnet")]
+use ipnet::{Ipv4Net, Ipv6Net};
+
+#[cfg(feature = "ipnet")]
+use std::convert::TryFrom;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+impl Record {
+    /// Returns the first address of this record if it describes a range of IPv4 addresses.
+    pub fn start_ipv4(&self) -> Option<Ipv4Addr> {
+        match self.res_type {
+            Type::IPv4 => self.start.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the first address of this record if it describes an IPv6 prefix.
+    pub fn start_ipv6(&self) -> Option<Ipv6Addr> {
+        match self.res_type {
+            Type::IPv6 => self.start.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the first and the last address of this record if it describes an IP range.
+    ///
+    /// For IPv4 the value is the number of addresses in the range, for IPv6 it is the prefix length.
+    /// Returns None if the value is zero, is not a valid prefix length or if the range would
+    /// exceed the address space. IPv6 prefixes whose start has any bits set after the prefix
+    /// length are rejected as well.
+    pub fn address_range(&self) -> Option<(IpAddr, IpAddr)> {
+        match self.res_type {
+            Type::IPv4 => self
+                .ipv4_range()
+                .map(|(start, end)| (Ipv4Addr::from(start).into(), Ipv4Addr::from(end).into())),
+            Type::IPv6 => self
+                .ipv6_range()
+                .map(|(start, end)| (Ipv6Addr::from(start).into(), Ipv6Addr::from(end).into())),
+            _ => None,
+        }
+    }
+
+    /// Returns the last address of this record if it describes an IP range, see `address_range`.
+    pub fn end_address(&self) -> Option<IpAddr> {
+        match self.res_type {
+            Type::IPv4 => self.ipv4_range().map(|(_, end)| Ipv4Addr::from(end).into()),
+            Type::IPv6 => self.ipv6_range().map(|(_, end)| Ipv6Addr::from(end).into()),
+            _ => None,
+        }
+    }
+
+    /// Returns the first and the last AS number of this record if it describes a block of AS
+    /// numbers.
+    ///
+    /// Returns None if the start is not a valid AS number, the value is zero or if the block would
+    /// exceed the 32-bit AS number space.
+    pub fn asn_range(&self) -> Option<(u32, u32)> {
+        if self.res_type != Type::ASN || self.value == 0 {
+            return None;
+        }
+
+        let start = self.start.parse::<u32>().ok()?;
+        let end = start.checked_add(self.value - 1)?;
+        Some((start, end))
+    }
+
+    /// Decomposes the range of an IPv4 record into the minimal set of aligned CIDR blocks.
+    ///
+    /// Returns an empty Vec if the record does not describe a valid range of IPv4 addresses.
+    #[cfg(feature = "ipnet")]
+    pub fn ipv4_cidrs(&self) -> Vec<Ipv4Net> {
+        let mut cidrs = Vec::new();
+
+        let (start, end) = match self.ipv4_range() {
+            Some((start, end)) => (u64::from(start), u64::from(end)),
+            None => return cidrs,
+        };
+
+        let mut current = start;
+        while current <= end {
+            // Take the largest block that is aligned on the current address and fits in the range.
+            let mut size = 1u64 << current.trailing_zeros().min(32);
+            while current + size - 1 > end {
+                size >>= 1;
+            }
+
+            let prefix = 32 - size.trailing_zeros() as u8;
+            cidrs.push(Ipv4Net::new(Ipv4Addr::from(current as u32), prefix).unwrap());
+            current += size;
+        }
+
+        cidrs
+    }
+
+    /// Returns the IPv6 prefix of this record, combining its start with its prefix length.
+    ///
+    /// Returns None for the same records as `address_range`: if the record is not an IPv6
+    /// record, its prefix length is zero or exceeds 128, or its start has any bits set after the
+    /// prefix length.
+    #[cfg(feature = "ipnet")]
+    pub fn ipv6_net(&self) -> Option<Ipv6Net> {
+        let (start, _) = self.ipv6_range()?;
+        let prefix = u8::try_from(self.value).ok()?;
+        Ipv6Net::new(Ipv6Addr::from(start), prefix).ok()
+    }
+
+    /// Returns the first and last IPv4 address of this record as integers.
+    pub(crate) fn ipv4_range(&self) -> Option<(u32, u32)> {
+        let start = u32::from(self.start_ipv4()?);
+
+        if self.value == 0 {
+            return None;
+        }
+
+        let end = u64::from(start) + self.value_u64() - 1;
+        if end > u64::from(u32::MAX) {
+            return None;
+        }
+
+        Some((start, end as u32))
+    }
+
+    /// Returns the first and last IPv6 address of this record as integers.
+    pub(crate) fn ipv6_range(&self) -> Option<(u128, u128)> {
+        let start = u128::from(self.start_ipv6()?);
+
+        if self.value == 0 || self.value > 128 {
+            return None;
+        }
+
+        let hosts = u128::MAX.checked_shr(self.value).unwrap_or(0);
+        if start & hosts != 0 {
+            return None;
+        }
+
+        Some((start, start | hosts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Record, Status, Type};
+
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    fn record(res_type: Type, start: &str, value: u32) -> Record {
+        Record {
+            registry: "ripencc".to_string(),
+            organization: "NL".to_string(),
+            res_type,
+            start: start.to_string(),
+            value,
+            date: "19930901".to_string(),
+            status: Status::Assigned,
+            id: "".to_string(),
+            flags: None,
+            extra: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_start_ipv4() {
+        let ipv4 = record(Type::IPv4, "193.0.0.0", 256);
+        assert_eq!(ipv4.start_ipv4(), Some(Ipv4Addr::new(193, 0, 0, 0)));
+
+        let asn = record(Type::ASN, "3333", 1);
+        assert_eq!(asn.start_ipv4(), None);
+    }
+
+    #[test]
+    fn test_start_ipv6() {
+        let ipv6 = record(Type::IPv6, "2001:67c:2e8::", 48);
+        assert_eq!(
+            ipv6.start_ipv6(),
+            Some(Ipv6Addr::new(0x2001, 0x67c, 0x2e8, 0, 0, 0, 0, 0))
+        );
+
+        let ipv4 = record(Type::IPv4, "193.0.0.0", 256);
+        assert_eq!(ipv4.start_ipv6(), None);
+    }
+
+    #[test]
+    fn test_address_range() {
+        let ipv4 = record(Type::IPv4, "193.0.0.0", 768);
+        assert_eq!(
+            ipv4.address_range(),
+            Some((
+                IpAddr::V4(Ipv4Addr::new(193, 0, 0, 0)),
+                IpAddr::V4(Ipv4Addr::new(193, 0, 2, 255))
+            ))
+        );
+
+        let ipv6 = record(Type::IPv6, "2001:67c:2e8::", 48);
+        assert_eq!(
+            ipv6.address_range(),
+            Some((
+                IpAddr::V6(Ipv6Addr::new(0x2001, 0x67c, 0x2e8, 0, 0, 0, 0, 0)),
+                IpAddr::V6(Ipv6Addr::new(
+                    0x2001, 0x67c, 0x2e8, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff
+                ))
+            ))
+        );
+
+        assert_eq!(record(Type::IPv4, "0.0.0.0", 0).address_range(), None);
+        assert_eq!(
+            record(Type::IPv4, "255.255.255.0", 512).address_range(),
+            None
+        );
+        assert_eq!(
+            record(Type::IPv6, "2001:67c:2e8::", 129).address_range(),
+            None
+        );
+        assert_eq!(record(Type::ASN, "3333", 1).address_range(), None);
+    }
+
+    #[test]
+    fn test_asn_range() {
+        assert_eq!(record(Type::ASN, "3333", 1).asn_range(), Some((3333, 3333)));
+        assert_eq!(
+            record(Type::ASN, "196608", 1024).asn_range(),
+            Some((196608, 197631))
+        );
+
+        assert_eq!(record(Type::ASN, "3333", 0).asn_range(), None);
+        assert_eq!(record(Type::ASN, "AS3333", 1).asn_range(), None);
+        assert_eq!(record(Type::ASN, "4294967295", 2).asn_range(), None);
+        assert_eq!(record(Type::IPv4, "193.0.0.0", 256).asn_range(), None);
+    }
+
+    #[test]
+    fn test_end_address() {
+        assert_eq!(
+            record(Type::IPv4, "193.0.0.0", 768).end_address(),
+            Some(IpAddr::V4(Ipv4Addr::new(193, 0, 2, 255)))
+        );
+        assert_eq!(
+            record(Type::IPv6, "2001:db8::", 32).end_address(),
+            Some(IpAddr::V6(Ipv6Addr::new(
+                0x2001, 0xdb8, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff
+            )))
+        );
+        assert_eq!(record(Type::IPv4, "255.255.255.0", 512).end_address(), None);
+        assert_eq!(record(Type::ASN, "3333", 1).end_address(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "ipnet")]
+    fn test_ipv4_cidrs() {
+        let cidrs = record(Type::IPv4, "192.0.2.0", 768)
+            .ipv4_cidrs()
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(cidrs, vec!["192.0.2.0/23", "192.0.4.0/24"]);
+
+        let cidrs = record(Type::IPv4, "192.0.3.0", 768)
+            .ipv4_cidrs()
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(cidrs, vec!["192.0.3.0/24", "192.0.4.0/23"]);
+
+        assert_eq!(record(Type::IPv4, "0.0.0.0", 0).ipv4_cidrs(), vec![]);
+        assert_eq!(record(Type::ASN, "3333", 1).ipv4_cidrs(), vec![]);
+    }
+
+    #[test]
+    #[cfg(feature = "ipnet")]
+    fn test_ipv6_net() {
+        let net = record(Type::IPv6, "2001:67c:2e8::", 48).ipv6_net().unwrap();
+        assert_eq!(net.to_string(), "2001:67c:2e8::/48");
+        assert!(net.contains(&"2001:67c:2e8:22::c100:68b".parse::<Ipv6Addr>().unwrap()));
+
+        let net = record(Type::IPv6, "2001:db8::1", 128).ipv6_net().unwrap();
+        assert_eq!(net.to_string(), "2001:db8::1/128");
+
+        assert_eq!(record(Type::IPv6, "2001:67c:2e8::", 129).ipv6_net(), None);
+        assert_eq!(record(Type::IPv4, "193.0.0.0", 48).ipv6_net(), None);
+    }
+
+    #[test]
+    fn test_ipv6_invalid_prefix() {
+        // A prefix length of zero, and a start with bits set after the prefix length.
+        for (start, value) in &[("::", 0), ("2001:67c:2e8::1", 48)] {
+            let record = record(Type::IPv6, start, *value);
+            assert_eq!(record.address_range(), None);
+            assert_eq!(record.end_address(), None);
+            #[cfg(feature = "ipnet")]
+            assert_eq!(record.ipv6_net(), None);
+        }
+
+        assert_eq!(
+            record(Type::IPv6, "2001:db8::1", 128).address_range(),
+            Some((
+                IpAddr::V6("2001:db8::1".parse().unwrap()),
+                IpAddr::V6("2001:db8::1".parse().unwrap())
+            ))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "ipnet")]
+    fn test_ipv6_invalid_prefix_length() {
+        // A host count mistakenly placed in the value field of an IPv6 record.
+        let lines =
+            crate::read_all("apnic|JP|ipv6|2001:200::|200|19990813|allocated|abc\n".as_bytes())
+                .unwrap()
+                .collect::<Vec<_>>();
+
+        match &lines[0] {
+            crate::Line::Record(record) => {
+                assert_eq!(record.value, 200);
+                assert_eq!(record.ipv6_net(), None);
+                assert_eq!(record.address_range(), None);
+            }
+            _ => panic!("Expected a record line"),
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//!
+//! When the `iso3166` feature is enabled, functionality is provided to check that the country codes
+//! of records are ISO 3166-1 alpha-2 codes.
+//!
+
+use crate::{records, Line, Record};
+
+/// The ISO 3166-1 alpha-2 codes that are officially assigned to a country or territory, sorted.
+const COUNTRY_CODES: [&str; 249] = [
+    "AD", "AE", "AF", "AG", "AI", "AL", "AM", "AO", "AQ", "AR", "AS", "AT", "AU", "AW", "AX", "AZ",
+    "BA", "BB", "BD", "BE", "BF", "BG", "BH", "BI", "BJ", "BL", "BM", "BN", "BO", "BQ", "BR", "BS",
+    "BT", "BV", "BW", "BY", "BZ", "CA", "CC", "CD", "CF", "CG", "CH", "CI", "CK", "CL", "CM", "CN",
+    "CO", "CR", "CU", "CV", "CW", "CX", "CY", "CZ", "DE", "DJ", "DK", "DM", "DO", "DZ", "EC", "EE",
+    "EG", "EH", "ER", "ES", "ET", "FI", "FJ", "FK", "FM", "FO", "FR", "GA", "GB", "GD", "GE", "GF",
+    "GG", "GH", "GI", "GL", "GM", "GN", "GP", "GQ", "GR", "GS", "GT", "GU", "GW", "GY", "HK", "HM",
+    "HN", "HR", "HT", "HU", "ID", "IE", "IL", "IM", "IN", "IO", "IQ", "IR", "IS", "IT", "JE", "JM",
+    "JO", "JP", "KE", "KG", "KH", "KI", "KM", "KN", "KP", "KR", "KW", "KY", "KZ", "LA", "LB", "LC",
+    "LI", "LK", "LR", "LS", "LT", "LU", "LV", "LY", "MA", "MC", "MD", "ME", "MF", "MG", "MH", "MK",
+    "ML", "MM", "MN", "MO", "MP", "MQ", "MR", "MS", "MT", "MU", "MV", "MW", "MX", "MY", "MZ", "NA",
+    "NC", "NE", "NF", "NG", "NI", "NL", "NO", "NP", "NR", "NU", "NZ", "OM", "PA", "PE", "PF", "PG",
+    "PH", "PK", "PL", "PM", "PN", "PR", "PS", "PT", "PW", "PY", "QA", "RE", "RO", "RS", "RU", "RW",
+    "SA", "SB", "SC", "SD", "SE", "SG", "SH", "SI", "SJ", "SK", "SL", "SM", "SN", "SO", "SR", "SS",
+    "ST", "SV", "SX", "SY", "SZ", "TC", "TD", "TF", "TG", "TH", "TJ", "TK", "TL", "TM", "TN", "TO",
+    "TR", "TT", "TV", "TW", "TZ", "UA", "UG", "UM", "US", "UY", "UZ", "VA", "VC", "VE", "VG", "VI",
+    "VN", "VU", "WF", "WS", "YE", "YT", "ZA", "ZM", "ZW",
+];
+
+/// The codes that are not assigned to a country but that the registries use in their listings:
+/// `AP` for the Asia Pacific region, `EU` for the European Union and `ZZ` for resources that are
+/// not assigned to any country.
+const REGISTRY_CODES: [&str; 3] = ["AP", "EU", "ZZ"];
+
+///
+/// Returns whether the code is an officially assigned ISO 3166-1 alpha-2 code or one of the codes
+/// `AP`, `EU` and `ZZ` that the registries use for resources not assigned to a single country.
+/// Codes are matched case-sensitively, as the registries always use uppercase codes.
+///
+pub fn is_country_code(code: &str) -> bool {
+    COUNTRY_CODES.binary_search(&code).is_ok() || REGISTRY_CODES.contains(&code)
+}
+
+///
+/// Returns the records found in the given lines whose country code is not recognized by
+/// `is_country_code`, such as records that are empty or contain an organization handle instead.
+///
+pub fn invalid_country_codes(lines: &[Line]) -> Vec<&Record> {
+    records(lines)
+        .filter(|record| !is_country_code(&record.organization))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{invalid_country_codes, is_country_code, read_all, Line};
+
+    #[test]
+    fn test_is_country_code() {
+        assert!(is_country_code("NL"));
+        assert!(is_country_code("AD"));
+        assert!(is_country_code("ZW"));
+        assert!(is_country_code("ZZ"));
+        assert!(!is_country_code("nl"));
+        assert!(!is_country_code("XX"));
+        assert!(!is_country_code(""));
+    }
+
+    #[test]
+    fn test_invalid_country_codes() {
+        let lines = read_all(
+            "ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+             ripencc||ipv4|193.0.4.0|256|19930901|available|\n\
+             ripencc|ORG-RIEN1-RIPE|asn|3333|1|19930901|assigned|abc\n\
+             ripencc|EU|asn|3320|1|19930901|assigned|abc\n"
+                .as_bytes(),
+        )
+        .unwrap()
+        .collect::<Vec<Line>>();
+
+        let invalid = invalid_country_codes(&lines)
+            .iter()
+            .map(|record| record.start.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(invalid, vec!["193.0.4.0", "3333"]);
+    }
+}
@@ -0,0 +1,101 @@
+//!
+//! When the `csv` feature is enabled, functionality is provided to export the records of parsed
+//! RSEF listings as CSV, e.g. to import them into spreadsheets or databases.
+//!
+
+use crate::{records, Line, RsefError};
+
+use std::borrow::Cow;
+use std::io::Write;
+
+/// The names of the columns of the exported records, in order.
+const HEADER: [&str; 8] = [
+    "registry", "country", "type", "start", "value", "date", "status", "id",
+];
+
+///
+/// Writes the records found in the given lines to a stream as CSV, preceded by a header row.
+/// Version and summary lines are skipped. Fields that contain a comma, a double quote or a line
+/// break are quoted as described by RFC 4180.
+///
+/// # Example
+/// ```
+/// let listing = "ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n";
+/// let lines = rsef_rs::read_all(listing.as_bytes()).unwrap().collect::<Vec<_>>();
+///
+/// let mut output = Vec::new();
+/// rsef_rs::write_csv(&lines, &mut output).unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "registry,country,type,start,value,date,status,id\r\n\
+///      ripencc,NL,ipv4,193.0.0.0,256,19930901,assigned,abc\r\n"
+/// );
+/// ```
+///
+pub fn write_csv<W: Write>(lines: &[Line], mut out: W) -> Result<(), RsefError> {
+    write_row(&mut out, &HEADER)?;
+
+    for record in records(lines) {
+        write_row(
+            &mut out,
+            &[
+                &record.registry,
+                &record.organization,
+                &record.res_type.to_string(),
+                &record.start,
+                &record.value.to_string(),
+                &record.date,
+                &record.status.to_string(),
+                &record.id,
+            ],
+        )?;
+    }
+
+    Ok(out.flush()?)
+}
+
+/// Writes a single row of fields, terminated by CRLF.
+fn write_row<W: Write>(out: &mut W, fields: &[&str]) -> std::io::Result<()> {
+    let row = fields
+        .iter()
+        .map(|field| escape(field))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    write!(out, "{}\r\n", row)
+}
+
+/// Quotes a field if it contains a character that has a special meaning in CSV.
+fn escape(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\r', '\n']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{read_all, write_csv, Line};
+
+    #[test]
+    fn test_write_csv() {
+        let listing = "2|ripencc|1|2|19830705|20190201|+0100\n\
+                       ripencc|*|ipv4|*|2|summary\n\
+                       ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+                       ripencc|ZZ|ipv4|193.0.16.0|256||available|\"a,b\"\n";
+
+        let lines = read_all(listing.as_bytes()).unwrap().collect::<Vec<Line>>();
+
+        let mut output = Vec::new();
+        write_csv(&lines, &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "registry,country,type,start,value,date,status,id\r\n\
+             ripencc,NL,ipv4,193.0.0.0,256,19930901,assigned,abc\r\n\
+             ripencc,ZZ,ipv4,193.0.16.0,256,,available,\"\"\"a,b\"\"\"\r\n"
+        );
+    }
+}
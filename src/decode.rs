@@ -0,0 +1,222 @@
+//!
+//! Functionality to transparently decompress RSEF listings based on their contents.
+//!
+
+use crate::RsefError;
+
+use std::io::{Cursor, Read};
+#[cfg(feature = "download")]
+use tokio::io::AsyncRead;
+
+/// The magic bytes that gzip compressed data starts with.
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+/// The magic bytes that bzip2 compressed data starts with.
+const BZIP2_MAGIC: &[u8] = &[0x42, 0x5a, 0x68];
+
+/// The magic bytes that zstd compressed data starts with.
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+///
+/// Wraps a stream in the decoder matching the compression of its contents. The compression is
+/// detected by inspecting the first bytes of the stream: gzip, bzip2 and zstd compressed data is
+/// decompressed, anything else is returned as plain text.
+///
+/// Decompressing gzip requires the `libflate` feature and decompressing bzip2 requires the `bzip2`
+/// feature, both of which are enabled by the `download` feature. Decompressing zstd, as served by
+/// some mirrors, requires the `zstd` feature. Compressed data for which the decoder is not
+/// available results in an error rather than being parsed as plain text.
+///
+pub fn decode<R: Read + Send + 'static>(mut read: R) -> Result<Box<dyn Read + Send>, RsefError> {
+    let mut magic = Vec::with_capacity(MAGIC_LEN as usize);
+    read.by_ref().take(MAGIC_LEN).read_to_end(&mut magic)?;
+    let (compression, magic) = sniff(magic);
+    let stream = magic.chain(read);
+
+    match compression {
+        Some(Compression::Gzip) => {
+            #[cfg(feature = "libflate")]
+            return Ok(Box::new(libflate::gzip::Decoder::new(stream)?));
+
+            #[cfg(not(feature = "libflate"))]
+            return Err(unsupported("gzip", "libflate"));
+        }
+        Some(Compression::Bzip2) => {
+            #[cfg(feature = "bzip2")]
+            return Ok(Box::new(bzip2::read::BzDecoder::new(stream)));
+
+            #[cfg(not(feature = "bzip2"))]
+            return Err(unsupported("bzip2", "bzip2"));
+        }
+        Some(Compression::Zstd) => {
+            #[cfg(feature = "zstd")]
+            return Ok(Box::new(zstd::stream::read::Decoder::new(stream)?));
+
+            #[cfg(not(feature = "zstd"))]
+            return Err(unsupported("zstd", "zstd"));
+        }
+        None => Ok(Box::new(stream)),
+    }
+}
+
+///
+/// Wraps an asynchronous stream in the decoder matching the compression of its contents, like
+/// `decode` does for synchronous streams. The listing is decompressed as it is read, so reading
+/// from the returned stream never blocks the runtime.
+///
+/// Requires the `download` feature. Decompressing zstd requires the `zstd` feature as well.
+///
+#[cfg(feature = "download")]
+pub async fn decode_async<R>(mut read: R) -> Result<Box<dyn AsyncRead + Send + Unpin>, RsefError>
+where
+    R: AsyncRead + Send + Unpin + 'static,
+{
+    use async_compression::tokio::bufread::{BzDecoder, GzipDecoder};
+    use tokio::io::{AsyncReadExt, BufReader};
+
+    let mut magic = Vec::with_capacity(MAGIC_LEN as usize);
+    (&mut read).take(MAGIC_LEN).read_to_end(&mut magic).await?;
+    let (compression, magic) = sniff(magic);
+    let stream = BufReader::new(AsyncReadExt::chain(magic, read));
+
+    match compression {
+        Some(Compression::Gzip) => Ok(Box::new(GzipDecoder::new(stream))),
+        Some(Compression::Bzip2) => Ok(Box::new(BzDecoder::new(stream))),
+        Some(Compression::Zstd) => {
+            #[cfg(feature = "zstd")]
+            return Ok(Box::new(
+                async_compression::tokio::bufread::ZstdDecoder::new(stream),
+            ));
+
+            #[cfg(not(feature = "zstd"))]
+            return Err(unsupported("zstd", "zstd"));
+        }
+        None => Ok(Box::new(stream)),
+    }
+}
+
+/// The compression formats that `decode` and `decode_async` recognize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    Gzip,
+    Bzip2,
+    Zstd,
+}
+
+/// The number of bytes at the start of a stream that are inspected to detect its compression.
+const MAGIC_LEN: u64 = 4;
+
+/// Detects the compression of a stream from its first `MAGIC_LEN` bytes, or None if it is plain
+/// text. As these bytes were consumed from the stream, they are returned as a stream of their own
+/// to be put back in front of the rest of it.
+fn sniff(magic: Vec<u8>) -> (Option<Compression>, Cursor<Vec<u8>>) {
+    let compression = if magic.starts_with(GZIP_MAGIC) {
+        debug!("Detected gzip compressed listing");
+        Some(Compression::Gzip)
+    } else if magic.starts_with(BZIP2_MAGIC) {
+        debug!("Detected bzip2 compressed listing");
+        Some(Compression::Bzip2)
+    } else if magic.starts_with(ZSTD_MAGIC) {
+        debug!("Detected zstd compressed listing");
+        Some(Compression::Zstd)
+    } else {
+        debug!("Detected uncompressed listing");
+        None
+    };
+
+    (compression, Cursor::new(magic))
+}
+
+/// Creates the error returned for compressed data whose decoder has not been compiled in.
+#[cfg(not(all(feature = "libflate", feature = "bzip2", feature = "zstd")))]
+fn unsupported(compression: &str, feature: &str) -> RsefError {
+    RsefError::Io(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!(
+            "Decompressing {} data requires the '{}' feature",
+            compression, feature
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decode;
+
+    use std::io::Read;
+
+    const LISTING: &str = "ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n";
+
+    fn decode_to_string(data: Vec<u8>) -> String {
+        let mut output = String::new();
+        decode(std::io::Cursor::new(data))
+            .unwrap()
+            .read_to_string(&mut output)
+            .unwrap();
+        output
+    }
+
+    #[test]
+    fn test_decode_plain() {
+        assert_eq!(decode_to_string(LISTING.as_bytes().to_vec()), LISTING);
+        assert_eq!(decode_to_string(Vec::new()), "");
+    }
+
+    #[test]
+    #[cfg(feature = "libflate")]
+    fn test_decode_gzip() {
+        use std::io::Write;
+
+        let mut encoder = libflate::gzip::Encoder::new(Vec::new()).unwrap();
+        encoder.write_all(LISTING.as_bytes()).unwrap();
+        let data = encoder.finish().into_result().unwrap();
+
+        assert_eq!(decode_to_string(data), LISTING);
+    }
+
+    #[test]
+    #[cfg(feature = "bzip2")]
+    fn test_decode_bzip2() {
+        let mut data = Vec::new();
+        bzip2::read::BzEncoder::new(LISTING.as_bytes(), bzip2::Compression::Best)
+            .read_to_end(&mut data)
+            .unwrap();
+
+        assert_eq!(decode_to_string(data), LISTING);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "download")]
+    async fn test_decode_async() {
+        use std::io::Write;
+        use tokio::io::AsyncReadExt;
+
+        let mut encoder = libflate::gzip::Encoder::new(Vec::new()).unwrap();
+        encoder.write_all(LISTING.as_bytes()).unwrap();
+        let gzip = encoder.finish().into_result().unwrap();
+
+        let mut bzip2 = Vec::new();
+        bzip2::read::BzEncoder::new(LISTING.as_bytes(), bzip2::Compression::Best)
+            .read_to_end(&mut bzip2)
+            .unwrap();
+
+        for data in [LISTING.as_bytes().to_vec(), gzip, bzip2] {
+            let mut output = String::new();
+            crate::decode_async(std::io::Cursor::new(data))
+                .await
+                .unwrap()
+                .read_to_string(&mut output)
+                .await
+                .unwrap();
+            assert_eq!(output, LISTING);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn test_decode_zstd() {
+        let data = zstd::stream::encode_all(LISTING.as_bytes(), 0).unwrap();
+
+        assert_eq!(decode_to_string(data), LISTING);
+    }
+}
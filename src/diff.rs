@@ -0,0 +1,98 @@
+//!
+//! Functionality to compare two RSEF listings, e.g. the listings of a registry on two days.
+//!
+
+use crate::{records, Line, Record, Type};
+
+use std::collections::HashMap;
+
+/// Describes the differences between an old and a new RSEF listing.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ListingDiff<'a> {
+    /// The records that are only present in the new listing.
+    pub added: Vec<&'a Record>,
+
+    /// The records that are only present in the old listing.
+    pub removed: Vec<&'a Record>,
+
+    /// The records whose status, value or organization changed, as (old, new) pairs.
+    pub changed: Vec<(&'a Record, &'a Record)>,
+}
+
+///
+/// Compares the records of two RSEF listings. Records are matched on their resource type and
+/// start, so a record that changed in any other way is reported as changed rather than as
+/// removed and added.
+///
+pub fn diff<'a>(old: &'a [Line], new: &'a [Line]) -> ListingDiff<'a> {
+    let old_records = index(old);
+    let new_records = index(new);
+    let mut result = ListingDiff::default();
+
+    for record in records(new) {
+        match old_records.get(&key(record)) {
+            None => result.added.push(record),
+            Some(previous) => {
+                if previous.status != record.status
+                    || previous.value != record.value
+                    || previous.organization != record.organization
+                {
+                    result.changed.push((previous, record));
+                }
+            }
+        }
+    }
+
+    for record in records(old) {
+        if !new_records.contains_key(&key(record)) {
+            result.removed.push(record);
+        }
+    }
+
+    result
+}
+
+/// Returns the key on which records of two listings are matched.
+fn key(record: &Record) -> (Type, &str) {
+    (record.res_type, &record.start)
+}
+
+/// Indexes the records of a listing by their key.
+fn index(lines: &[Line]) -> HashMap<(Type, &str), &Record> {
+    records(lines).map(|record| (key(record), record)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{diff, read_all, Line, Status};
+
+    fn parse(listing: &str) -> Vec<Line> {
+        read_all(listing.as_bytes()).unwrap().collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = parse(
+            "ripencc|NL|ipv4|193.0.0.0|256|19930901|allocated|abc\n\
+             ripencc|NL|ipv4|193.0.4.0|256|19930901|assigned|abc\n\
+             ripencc|NL|asn|3333|1|19930901|assigned|abc\n",
+        );
+        let new = parse(
+            "ripencc|NL|ipv4|193.0.0.0|256|19930901|allocated|abc\n\
+             ripencc|NL|ipv4|193.0.4.0|256|19930901|available|\n\
+             ripencc|DE|asn|3320|1|19930901|assigned|def\n",
+        );
+
+        let result = diff(&old, &new);
+
+        assert_eq!(result.added.len(), 1);
+        assert_eq!(result.added[0].start, "3320");
+
+        assert_eq!(result.removed.len(), 1);
+        assert_eq!(result.removed[0].start, "3333");
+
+        assert_eq!(result.changed.len(), 1);
+        assert_eq!(result.changed[0].0.status, Status::Assigned);
+        assert_eq!(result.changed[0].1.status, Status::Available);
+    }
+}
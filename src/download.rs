@@ -8,9 +8,11 @@
 //! ```
 //! use rsef_rs::{Registry, Line};
 //!
+//! # #[tokio::main]
+//! # async fn main() {
 //! // Friday 1 February 2019 21:22:48
 //! let timestamp = 1_549_056_168;
-//! let stream = Registry::RIPE.download(timestamp).unwrap();
+//! let stream = Registry::RIPE.download(timestamp).await.unwrap();
 //! let records = rsef_rs::read_all(stream).unwrap();
 //!
 //! for x in records {
@@ -20,126 +22,2060 @@
 //!         Line::Record(x) => println!("Record: {:?}", x),
 //!     }
 //! }
+//! # }
 //! ```
 
-use bzip2::read::BzDecoder;
 use chrono::DateTime;
-use chrono::Datelike;
-use chrono::NaiveDateTime;
+use chrono::NaiveDate;
 use chrono::Utc;
-use libflate::gzip::Decoder;
+use futures_util::TryStreamExt;
+use reqwest::header::{HeaderMap, HeaderName};
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RANGE};
+use reqwest::StatusCode;
+use reqwest::{Client, ClientBuilder, RequestBuilder, Response};
+use tokio::io::AsyncRead;
+use tokio_util::io::StreamReader;
 
+use std::collections::HashMap;
 use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::io::Cursor;
 use std::io::Read;
+use std::str::FromStr;
+use std::task::Poll;
+use std::time::Duration;
 
-/// Represents a Regional Internet Registry (RIR).
+/// The User-Agent that downloads identify themselves with, unless another one is configured with
+/// `DownloadConfig::with_user_agent`.
+pub const USER_AGENT: &str = concat!("rsef-rs/", env!("CARGO_PKG_VERSION"));
+
+/// The time after which `Registry::download_range` gives up on the download of a single date.
+pub const RANGE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Represents a Regional Internet Registry (RIR), or any other source of RSEF listings such as a
+/// National Internet Registry (NIR) or an archive.
 #[allow(missing_docs)]
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Registry {
     AFRINIC,
     APNIC,
     ARIN,
     LACNIC,
     RIPE,
+
+    /// The Number Resource Organization (NRO), which publishes a combined listing of the records
+    /// of all the Regional Internet Registries. It only publishes an extended listing, which is
+    /// downloaded for classic listings as well.
+    NRO,
+
+    /// A source that publishes its listings at the URLs described by a template. The placeholders
+    /// `{year}`, `{month}` and `{day}` are replaced by the parts of the date of the listing and
+    /// `{date}` is replaced by the whole date formatted as `YYYYMMDD`, e.g.
+    /// `https://example.com/stats/{year}/delegated-example-extended-{date}.gz`.
+    Custom {
+        url_template: String,
+    },
 }
 
 impl Registry {
+    /// Returns all the Regional Internet Registries, e.g. to download the listings of every RIR.
+    /// The NRO is not included, as its listing combines the listings of these registries.
+    ///
+    /// ```
+    /// use rsef_rs::Registry;
+    ///
+    /// for registry in Registry::all() {
+    ///     println!("{}", registry);
+    /// }
+    /// ```
+    pub fn all() -> [Registry; 5] {
+        [
+            Registry::AFRINIC,
+            Registry::APNIC,
+            Registry::ARIN,
+            Registry::LACNIC,
+            Registry::RIPE,
+        ]
+    }
+
     /// Downloads the RSEF listings of a specific Regional Internet Registry at a specific moment.
     /// The timestamp should be an UNIX Epoch. Returns a decoded stream that can be read from.
     /// Only the year, month and day wll be used to select the listing for that day.
-    pub fn download(&self, timestamp: i64) -> Result<Box<dyn Read>, Box<dyn Error>> {
-        let datetime: DateTime<Utc> =
-            DateTime::from_utc(NaiveDateTime::from_timestamp(timestamp, 0), Utc);
-        let year = datetime.year();
-        let month = if datetime.month() < 10 {
-            format!("0{}", datetime.month())
-        } else {
-            format!("{}", datetime.month())
+    ///
+    /// Whether the listing is gzip or bzip2 compressed is detected from its contents, so listings
+    /// are decoded correctly even when a registry changes the compression it uses.
+    ///
+    /// The listing is transferred asynchronously and buffered in memory before it is decoded, so
+    /// reading from the returned stream never blocks on the network.
+    ///
+    /// The download is cancelled by dropping the returned future, e.g. when it loses a
+    /// `tokio::select!` or its task is aborted. This aborts the request and closes its connection
+    /// at whatever point the transfer was; as nothing is written outside of the future, no
+    /// partial listing is left behind.
+    ///
+    /// Returns `RsefError::ListingNotAvailable` if the registry has not published a listing for
+    /// the day, which is the case for days in the future and may be the case for today. Any other
+    /// HTTP error status is returned as `RsefError::HttpStatus`.
+    pub async fn download(
+        &self,
+        timestamp: i64,
+    ) -> Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>> {
+        self.download_for_date(to_date(timestamp)?).await
+    }
+
+    /// Downloads the RSEF listings of a specific Regional Internet Registry at a specific moment
+    /// like `download`, unless the given cancellation future completes first. In that case the
+    /// download is aborted as if its future was dropped and `RsefError::Cancelled` is returned.
+    /// The timestamp should be an UNIX Epoch.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rsef_rs::Registry;
+    /// use std::time::Duration;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// // Cancel the download on shutdown, here simulated by a timer.
+    /// let shutdown = tokio::time::sleep(Duration::from_secs(10));
+    /// match Registry::RIPE.download_cancellable(1_549_056_168, shutdown).await {
+    ///     Ok(stream) => println!("Downloaded the listing"),
+    ///     Err(err) => println!("Failed to download the listing: {}", err),
+    /// }
+    /// # }
+    /// ```
+    pub async fn download_cancellable(
+        &self,
+        timestamp: i64,
+        cancel: impl Future<Output = ()>,
+    ) -> Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>> {
+        let mut download = Box::pin(self.download(timestamp));
+        let mut cancel = Box::pin(cancel);
+
+        std::future::poll_fn(|cx| {
+            if let Poll::Ready(result) = download.as_mut().poll(cx) {
+                return Poll::Ready(result);
+            }
+
+            match cancel.as_mut().poll(cx) {
+                Poll::Ready(()) => {
+                    debug!("Download cancelled");
+                    Poll::Ready(Err(crate::RsefError::Cancelled.into()))
+                }
+                Poll::Pending => Poll::Pending,
+            }
+        })
+        .await
+    }
+
+    /// Downloads the RSEF listings of a specific Regional Internet Registry for a specific date.
+    /// Returns a decoded stream that can be read from.
+    pub async fn download_for_date(
+        &self,
+        date: NaiveDate,
+    ) -> Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>> {
+        self.fetch(&client()?, date).await
+    }
+
+    /// Downloads the RSEF listings of a specific Regional Internet Registry at a specific moment
+    /// using the given client, so connection pools, proxies and TLS settings can be reused.
+    /// The timestamp should be an UNIX Epoch. Returns a decoded stream that can be read from.
+    pub async fn download_with_client(
+        &self,
+        timestamp: i64,
+        client: &Client,
+    ) -> Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>> {
+        self.fetch(client, to_date(timestamp)?).await
+    }
+
+    /// Downloads the RSEF listings of a specific Regional Internet Registry at a specific moment,
+    /// giving up once the given timeout has elapsed. The timeout applies to the whole request,
+    /// from connecting until the body has been received. A timed out download returns a
+    /// `reqwest::Error` for which `is_timeout()` returns true.
+    pub async fn download_with_timeout(
+        &self,
+        timestamp: i64,
+        timeout: Duration,
+    ) -> Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>> {
+        let downloader = Downloader::builder().timeout(timeout).build()?;
+        downloader.download(self, to_date(timestamp)?).await
+    }
+
+    /// Downloads the RSEF listings of a specific Regional Internet Registry at a specific moment,
+    /// retrying with an exponential backoff when the download fails with a transient error.
+    /// Only network errors and 5xx responses are retried; any other failure, such as a 404 for a
+    /// listing that does not exist, is returned immediately.
+    pub async fn download_with_retry(
+        &self,
+        timestamp: i64,
+        policy: &RetryPolicy,
+    ) -> Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>> {
+        let downloader = Downloader::builder().retries(policy.clone()).build()?;
+        downloader.download(self, to_date(timestamp)?).await
+    }
+
+    /// Downloads the RSEF listings of a specific Regional Internet Registry for every date from
+    /// start up to and including end. Returns the outcome of every download along with its date.
+    /// Dates for which the registry has not published a listing (404) are left out, any other
+    /// failure is returned in place of the stream for that date.
+    ///
+    /// The download of every date gives up after `RANGE_TIMEOUT`, so a single slow date cannot
+    /// stall the others. Use `Downloader::download_range` to configure the timeout and retries.
+    pub async fn download_range(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Vec<(
+        NaiveDate,
+        Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>>,
+    )> {
+        match Downloader::builder().timeout(RANGE_TIMEOUT).build() {
+            Ok(downloader) => downloader.download_range(self, start, end).await,
+            Err(err) => vec![(start, Err(err.into()))],
+        }
+    }
+
+    /// Downloads the classic (non-extended) RSEF listings of a specific Regional Internet Registry
+    /// at a specific moment. Classic listings lack the opaque identifier, so the id of every
+    /// parsed record is empty. The timestamp should be an UNIX Epoch.
+    pub async fn download_classic(
+        &self,
+        timestamp: i64,
+    ) -> Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>> {
+        let date = to_date(timestamp)?;
+        self.fetch_url(&client()?, &self.url(date, false), date)
+            .await
+    }
+
+    /// Downloads the RSEF listings of a specific Regional Internet Registry at a specific moment,
+    /// calling the progress callback every time a part of the listing has been received. The
+    /// callback receives the number of bytes received so far and, if the server announced it,
+    /// the total size of the (compressed) listing. The timestamp should be an UNIX Epoch.
+    pub async fn download_with_progress<F>(
+        &self,
+        timestamp: i64,
+        mut progress: F,
+    ) -> Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>>
+    where
+        F: FnMut(u64, Option<u64>),
+    {
+        let date = to_date(timestamp)?;
+        let mut response = self
+            .request_listing(&client()?, &self.url(date, true), date, |request| request)
+            .await?
+            .error_for_status()
+            .map_err(|err| self.status_error(date, err.into()))?;
+        let total = response.content_length();
+        let mut body = Vec::new();
+
+        while let Some(chunk) = response.chunk().await? {
+            body.extend_from_slice(&chunk);
+            progress(body.len() as u64, total);
+        }
+
+        Ok(decode_listing(Cursor::new(body))?)
+    }
+
+    /// Downloads the RSEF listings of a specific Regional Internet Registry at a specific moment
+    /// from the mirror configured for it, or from its primary mirror if none is configured. The
+    /// request is identified with the configured User-Agent.
+    /// The timestamp should be an UNIX Epoch. Returns a decoded stream that can be read from.
+    pub async fn download_with_config(
+        &self,
+        timestamp: i64,
+        config: &DownloadConfig,
+    ) -> Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>> {
+        let downloader = Downloader::builder().config(config.clone()).build()?;
+        downloader.download(self, to_date(timestamp)?).await
+    }
+
+    /// Downloads the RSEF listings of a specific Regional Internet Registry at a specific moment,
+    /// returning the metadata of the response along with the decoded stream, e.g. to log what
+    /// was downloaded. The timestamp should be an UNIX Epoch.
+    pub async fn download_with_meta(
+        &self,
+        timestamp: i64,
+    ) -> Result<(Box<dyn Read + Send>, DownloadMeta), Box<dyn Error + Send + Sync>> {
+        let date = to_date(timestamp)?;
+        let response = self
+            .request_listing(&client()?, &self.url(date, true), date, |request| request)
+            .await?
+            .error_for_status()
+            .map_err(|err| self.status_error(date, err.into()))?;
+
+        let meta = DownloadMeta {
+            url: response.url().to_string(),
+            content_length: response.content_length(),
+            last_modified: header(response.headers(), LAST_MODIFIED),
+            etag: header(response.headers(), ETAG),
         };
-        let day = if datetime.month() < 10 {
-            format!("0{}", datetime.day())
-        } else {
-            format!("{}", datetime.day())
+        let body = response.bytes().await?;
+
+        Ok((decode_listing(Cursor::new(body))?, meta))
+    }
+
+    /// Downloads the RSEF listings of a specific Regional Internet Registry at a specific moment
+    /// and returns their decompressed contents, e.g. to store them or to pass them on to another
+    /// parser. The timestamp should be an UNIX Epoch.
+    ///
+    /// The listing is decompressed with `tokio::task::spawn_blocking`, so decompressing large
+    /// listings does not block the runtime. This must be called from within a tokio runtime.
+    pub async fn download_bytes(
+        &self,
+        timestamp: i64,
+    ) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let mut listing = self.download(timestamp).await?;
+        let bytes = tokio::task::spawn_blocking(move || {
+            let mut bytes = Vec::new();
+            listing.read_to_end(&mut bytes).map(|_| bytes)
+        })
+        .await??;
+
+        Ok(bytes)
+    }
+
+    /// Downloads the RSEF listings of a specific Regional Internet Registry at a specific moment
+    /// and returns their decompressed contents as a String. Returns an error if the listing is
+    /// not valid UTF-8. The timestamp should be an UNIX Epoch.
+    pub async fn download_string(
+        &self,
+        timestamp: i64,
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(String::from_utf8(self.download_bytes(timestamp).await?)?)
+    }
+
+    /// Downloads the RSEF listings of a specific Regional Internet Registry at a specific moment
+    /// and returns their decompressed contents as a `tokio::io::AsyncRead`, to be parsed with
+    /// `read_all_async` without blocking the runtime. The timestamp should be an UNIX Epoch.
+    ///
+    /// The listing is decompressed while it is received, see `decode_async`, so it is never held
+    /// in memory as a whole. Reading from the returned stream waits on the network as needed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rsef_rs::Registry;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// let stream = Registry::RIPE.download_async_reader(1_549_056_168).await?;
+    /// let lines = rsef_rs::read_all_async(stream).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_async_reader(
+        &self,
+        timestamp: i64,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>, Box<dyn Error + Send + Sync>> {
+        let date = to_date(timestamp)?;
+        let response = self
+            .request_listing(&client()?, &self.url(date, true), date, |request| request)
+            .await?
+            .error_for_status()
+            .map_err(|err| self.status_error(date, err.into()))?;
+
+        let body = response.bytes_stream().map_err(std::io::Error::other);
+        Ok(decode_listing_async(StreamReader::new(body)).await?)
+    }
+
+    /// Downloads the RSEF listings of a specific Regional Internet Registry at a specific moment
+    /// and returns them exactly as the registry served them, e.g. still bzip2 compressed for RIPE
+    /// NCC, to archive or re-host the original file. Use `decode` to decompress them later.
+    /// The timestamp should be an UNIX Epoch.
+    pub async fn download_raw(
+        &self,
+        timestamp: i64,
+    ) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let date = to_date(timestamp)?;
+        self.fetch_raw_url(&client()?, &self.url(date, true), date)
+            .await
+    }
+
+    /// Downloads and decodes the listing for a specific date using the given client.
+    async fn fetch(
+        &self,
+        client: &Client,
+        date: NaiveDate,
+    ) -> Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>> {
+        self.fetch_url(client, &self.url(date, true), date).await
+    }
+
+    /// Downloads the RSEF listings of a specific Regional Internet Registry at a specific moment
+    /// and verifies them against the md5 checksum the registry publishes alongside the listing.
+    /// Returns `RsefError::Checksum` if the downloaded listing does not match the checksum, which
+    /// indicates a corrupt or truncated download. The timestamp should be an UNIX Epoch.
+    ///
+    /// Requires the `checksum` feature.
+    #[cfg(feature = "checksum")]
+    pub async fn download_verified(
+        &self,
+        timestamp: i64,
+    ) -> Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>> {
+        let client = client()?;
+        let date = to_date(timestamp)?;
+        let response = self
+            .request_listing(&client, &self.url(date, true), date, |request| request)
+            .await?
+            .error_for_status()
+            .map_err(|err| self.status_error(date, err.into()))?;
+
+        // The checksum is published next to the listing, so it is downloaded from the fallback URL
+        // too if the listing was found there.
+        let url = format!("{}.md5", response.url());
+        let body = response.bytes().await?;
+        let checksum = fetch_bytes(&client, &url).await?;
+
+        let expected = parse_md5(&String::from_utf8_lossy(&checksum))
+            .ok_or("The checksum file does not contain an md5 digest")?;
+        let actual = format!("{:x}", md5::compute(&body));
+
+        if expected != actual {
+            return Err(crate::RsefError::Checksum { expected, actual }.into());
+        }
+
+        Ok(decode_listing(Cursor::new(body))?)
+    }
+
+    /// Downloads the RSEF listings of a specific Regional Internet Registry at a specific moment,
+    /// unless they did not change since they were last downloaded. Pass the validator returned by
+    /// a previous download to send a conditional request; without a validator the listing is
+    /// always downloaded. The timestamp should be an UNIX Epoch.
+    pub async fn download_if_modified(
+        &self,
+        timestamp: i64,
+        validator: Option<&Validator>,
+    ) -> Result<ConditionalDownload, Box<dyn Error + Send + Sync>> {
+        let date = to_date(timestamp)?;
+        let conditional = |request: RequestBuilder| match validator {
+            Some(Validator::ETag(etag)) => request.header(IF_NONE_MATCH, etag.as_str()),
+            Some(Validator::LastModified(date)) => request.header(IF_MODIFIED_SINCE, date.as_str()),
+            None => request,
+        };
+
+        let response = self
+            .request_listing(&client()?, &self.url(date, true), date, conditional)
+            .await?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(ConditionalDownload::NotModified);
+        }
+
+        let response = response
+            .error_for_status()
+            .map_err(|err| self.status_error(date, err.into()))?;
+        let validator = Validator::from_headers(response.headers());
+        let body = response.bytes().await?;
+
+        Ok(ConditionalDownload::Modified(
+            decode_listing(Cursor::new(body))?,
+            validator,
+        ))
+    }
+
+    /// Continues an interrupted download of the RSEF listings of a specific Regional Internet
+    /// Registry at a specific moment, skipping the given number of bytes that were already
+    /// received. The timestamp should be an UNIX Epoch.
+    ///
+    /// Returns the raw (still compressed) remainder of the listing, along with the offset in the
+    /// listing at which it starts. If the server does not support resuming downloads, the whole
+    /// listing is returned and the offset is 0. Once all the parts have been written to a file,
+    /// the listing can be read with `read_file`.
+    pub async fn download_resume(
+        &self,
+        timestamp: i64,
+        offset: u64,
+    ) -> Result<(Box<dyn Read + Send>, u64), Box<dyn Error + Send + Sync>> {
+        let date = to_date(timestamp)?;
+        let range = |request: RequestBuilder| request.header(RANGE, format!("bytes={}-", offset));
+        let response = self
+            .request_listing(&client()?, &self.url(date, true), date, range)
+            .await?;
+
+        let start = match response.status() {
+            StatusCode::PARTIAL_CONTENT => offset,
+            // The offset lies at or past the end of the listing, so nothing is left to download.
+            StatusCode::RANGE_NOT_SATISFIABLE => return Ok((Box::new(std::io::empty()), offset)),
+            _ => 0,
+        };
+
+        let body = response
+            .error_for_status()
+            .map_err(|err| self.status_error(date, err.into()))?
+            .bytes()
+            .await?;
+        Ok((Box::new(Cursor::new(body)), start))
+    }
+
+    /// Downloads and decodes the listing for a specific date found at the given URL using the given
+    /// client. If the listing is not found, it is downloaded from the fallback URL if there is one.
+    async fn fetch_url(
+        &self,
+        client: &Client,
+        url: &str,
+        date: NaiveDate,
+    ) -> Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>> {
+        let body = self.fetch_raw_url(client, url, date).await?;
+        Ok(decode_listing(Cursor::new(body))?)
+    }
+
+    /// Downloads the listing for a specific date found at the given URL using the given client,
+    /// without decoding it. If the listing is not found, it is downloaded from the fallback URL if
+    /// there is one.
+    async fn fetch_raw_url(
+        &self,
+        client: &Client,
+        url: &str,
+        date: NaiveDate,
+    ) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let body = self
+            .request_listing(client, url, date, |request| request)
+            .await
+            .and_then(Response::error_for_status)
+            .map_err(|err| self.status_error(date, err.into()))?
+            .bytes()
+            .await?;
+        Ok(body.into())
+    }
+
+    /// Requests the listing for a specific date found at the given URL using the given client,
+    /// after passing the request through `configure`, e.g. to add headers. If the listing is not
+    /// found, the same request is sent to the fallback URL if there is one. The response is
+    /// returned whatever its status, so the caller decides which statuses are errors.
+    async fn request_listing<F>(
+        &self,
+        client: &Client,
+        url: &str,
+        date: NaiveDate,
+        configure: F,
+    ) -> Result<Response, reqwest::Error>
+    where
+        F: Fn(RequestBuilder) -> RequestBuilder,
+    {
+        let response = send(configure(client.get(url))).await?;
+        if response.status() != StatusCode::NOT_FOUND {
+            return Ok(response);
+        }
+
+        let fallback = match self.fallback_url(url, date) {
+            Some(fallback) => fallback,
+            None => return Ok(response),
         };
+        drop(response);
+
+        debug!(%url, %fallback, "Listing not found, trying the fallback URL");
+        let response = send(configure(client.get(&fallback))).await?;
+        if response.status().is_success() {
+            debug!(url = %fallback, "Found the listing at the fallback URL");
+        }
 
+        Ok(response)
+    }
+
+    /// Requests the listing for a specific date found at the given URL using the given blocking
+    /// client. If the listing is not found, it is requested from the fallback URL if there is
+    /// one. The response is returned whatever its status.
+    #[cfg(feature = "blocking")]
+    fn request_listing_blocking(
+        &self,
+        client: &reqwest::blocking::Client,
+        url: &str,
+        date: NaiveDate,
+    ) -> Result<reqwest::blocking::Response, reqwest::Error> {
+        debug!(%url, "Sending request");
+        let response = client.get(url).send()?;
+        debug!(status = %response.status(), "Received response");
+        if response.status() != StatusCode::NOT_FOUND {
+            return Ok(response);
+        }
+
+        let fallback = match self.fallback_url(url, date) {
+            Some(fallback) => fallback,
+            None => return Ok(response),
+        };
+        drop(response);
+
+        debug!(%url, %fallback, "Listing not found, trying the fallback URL");
+        let response = client.get(&fallback).send()?;
+        debug!(status = %response.status(), "Received response");
+        if response.status().is_success() {
+            debug!(url = %fallback, "Found the listing at the fallback URL");
+        }
+
+        Ok(response)
+    }
+
+    /// Returns the URL to try when the listing of a specific date is not found at the given URL.
+    /// AFRINIC publishes its listings in a directory per year, but some listings of older years
+    /// are found directly in its stats directory instead.
+    fn fallback_url(&self, url: &str, date: NaiveDate) -> Option<String> {
         match self {
             Registry::AFRINIC => {
-                let url = format!(
-                    "https://ftp.afrinic.net/pub/stats/afrinic/{}/delegated-afrinic-extended-{}{}{}",
-                    year, year, month, day
-                );
+                let year_directory = format!("/{}/delegated-afrinic", date.format("%Y"));
+                if url.contains(&year_directory) {
+                    Some(url.replacen(&year_directory, "/delegated-afrinic", 1))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Converts the error of a download that failed with an HTTP error status into
+    /// `RsefError::ListingNotAvailable` for 404 Not Found, or into `RsefError::HttpStatus` for any
+    /// other status. Any other error is returned unchanged.
+    fn status_error(
+        &self,
+        date: NaiveDate,
+        err: Box<dyn Error + Send + Sync>,
+    ) -> Box<dyn Error + Send + Sync> {
+        let response = err
+            .downcast_ref::<reqwest::Error>()
+            .and_then(|err| Some((err.status()?, err.url()?.to_string())));
 
-                let response = reqwest::get(url.as_str())?;
-                Ok(Box::new(response))
+        let (status, url) = match response {
+            Some(response) => response,
+            None => return err,
+        };
+
+        if status == StatusCode::NOT_FOUND {
+            let registry = self.clone();
+            return crate::RsefError::ListingNotAvailable { registry, date }.into();
+        }
+
+        crate::RsefError::HttpStatus { status, url }.into()
+    }
+
+    /// Downloads the RSEF listings of a specific Regional Internet Registry at a specific moment
+    /// without requiring an async runtime. The timestamp should be an UNIX Epoch. Returns a
+    /// decoded stream that reads the listing from the network as it is consumed.
+    ///
+    /// Requires the `blocking` feature. Like all blocking reqwest calls, this must not be called
+    /// from within an async runtime.
+    #[cfg(feature = "blocking")]
+    pub fn download_blocking(
+        &self,
+        timestamp: i64,
+    ) -> Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>> {
+        let date = to_date(timestamp)?;
+        let client = reqwest::blocking::Client::builder()
+            .user_agent(USER_AGENT)
+            .build()?;
+        let response = self
+            .request_listing_blocking(&client, &self.url(date, true), date)?
+            .error_for_status()
+            .map_err(|err| self.status_error(date, err.into()))?;
+        Ok(decode_listing(response)?)
+    }
+
+    /// Downloads the most recent RSEF listing of a specific Regional Internet Registry.
+    ///
+    /// APNIC, ARIN and AFRINIC publish the listing of the current (UTC) day, while RIPE, LACNIC and
+    /// the NRO lag a day behind, so the listing of yesterday is selected for them. If the selected listing
+    /// is not available yet, the listing of the day before is downloaded instead.
+    pub async fn download_latest(
+        &self,
+    ) -> Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>> {
+        let date = self.latest_date();
+
+        match self.download_for_date(date).await {
+            Err(err) if is_not_found(err.as_ref()) => {
+                let previous = date.pred_opt().ok_or("Invalid date")?;
+                self.download_for_date(previous).await
             }
-            Registry::APNIC => {
-                let url = format!(
-                    "https://ftp.apnic.net/stats/apnic/{}/delegated-apnic-extended-{}{}{}.gz",
-                    year, year, month, day
-                );
+            result => result,
+        }
+    }
+
+    /// Downloads and parses the RSEF listings of all the Regional Internet Registries, see
+    /// `Registry::all`, for a specific date. The listings are downloaded concurrently on tasks of
+    /// their own and parsed with `tokio::task::spawn_blocking`, so this must be called from within
+    /// a tokio runtime. The outcome of every download is returned for its registry, so a failed
+    /// download does not affect the others. Use `Downloader::download_all` to download the
+    /// listings from mirrors.
+    ///
+    /// Dates after the most recent listing of a registry, such as dates in the future, are
+    /// replaced by the date of its most recent listing. As RIPE NCC and LACNIC publish their
+    /// listing of a day only on the day after, the listing of the day before is downloaded for
+    /// them when the date is the current (UTC) day, like `download_latest` does.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rsef_rs::Registry;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let today = chrono::Utc::now().date_naive();
+    ///
+    /// for (registry, lines) in Registry::download_all(today).await {
+    ///     match lines {
+    ///         Ok(lines) => println!("{}: {} lines", registry, lines.len()),
+    ///         Err(err) => println!("{}: {}", registry, err),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn download_all(
+        date: NaiveDate,
+    ) -> HashMap<Registry, Result<Vec<crate::Line>, crate::RsefError>> {
+        match Downloader::builder().build() {
+            Ok(downloader) => downloader.download_all(date).await,
+            // A reqwest::Error cannot be cloned, so every registry receives its description.
+            Err(err) => Registry::all()
+                .iter()
+                .map(|registry| {
+                    let err = std::io::Error::other(err.to_string());
+                    (registry.clone(), Err(crate::RsefError::Io(err)))
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the date of the most recent listing this registry is expected to have published.
+    fn latest_date(&self) -> NaiveDate {
+        let today = Utc::now().date_naive();
 
-                let response = reqwest::get(url.as_str())?;
-                Ok(Box::new(Decoder::new(response)?))
+        match self {
+            Registry::RIPE | Registry::LACNIC | Registry::NRO => today.pred_opt().unwrap_or(today),
+            _ => today,
+        }
+    }
+
+    /// Returns the URL of the listing of this registry for a specific date on its primary mirror.
+    /// Extended listings include the opaque identifiers, classic listings do not.
+    fn url(&self, date: NaiveDate, extended: bool) -> String {
+        self.url_on(self.base_url(), date, extended)
+    }
+
+    /// Returns the URL of the listing of this registry for a specific date on the mirror found at
+    /// the given base URL. Custom registries ignore the base URL, as their URL template already
+    /// describes the whole URL, and always select the listing their template describes.
+    fn url_on(&self, base_url: &str, date: NaiveDate, extended: bool) -> String {
+        let base_url = base_url.trim_end_matches('/');
+        let year = date.format("%Y");
+        let month = date.format("%m");
+        let day = date.format("%d");
+        let date = date.format("%Y%m%d");
+        let kind = if extended { "-extended" } else { "" };
+
+        let url = match self {
+            Registry::AFRINIC => {
+                format!("{}/{}/delegated-afrinic{}-{}", base_url, year, kind, date)
             }
-            Registry::ARIN => {
-                let url = format!(
-                    "https://ftp.arin.net/pub/stats/arin/delegated-arin-extended-{}{}{}",
-                    year, month, day
-                );
+            // Both the archived listings and the listing of the current day are gzip compressed,
+            // but like every listing they are decoded based on their contents, see `decode`.
+            Registry::APNIC => format!("{}/{}/delegated-apnic{}-{}.gz", base_url, year, kind, date),
+            Registry::ARIN => format!("{}/delegated-arin{}-{}", base_url, kind, date),
+            Registry::LACNIC => format!("{}/delegated-lacnic{}-{}", base_url, kind, date),
+            // Some mirrors serve these gzip compressed despite the extension, which is handled by
+            // decoding the listing based on its contents rather than on the URL.
+            Registry::RIPE => format!(
+                "{}/{}/delegated-ripencc{}-{}.bz2",
+                base_url, year, kind, date
+            ),
+            Registry::NRO => format!("{}/{}/nro-delegated-stats", base_url, date),
+            Registry::Custom { url_template } => url_template
+                .replace("{year}", &year.to_string())
+                .replace("{month}", &month.to_string())
+                .replace("{day}", &day.to_string())
+                .replace("{date}", &date.to_string()),
+        };
+
+        debug!(registry = %self, %url, "Selected listing URL");
+        url
+    }
+
+    /// Returns the URL of the directory on the primary mirror of this registry that its listings
+    /// are published in.
+    fn base_url(&self) -> &'static str {
+        match self {
+            Registry::AFRINIC => "https://ftp.afrinic.net/pub/stats/afrinic",
+            Registry::APNIC => "https://ftp.apnic.net/stats/apnic",
+            Registry::ARIN => "https://ftp.arin.net/pub/stats/arin",
+            Registry::LACNIC => "https://ftp.lacnic.net/pub/stats/lacnic",
+            Registry::RIPE => "https://ftp.ripe.net/pub/stats/ripencc",
+            Registry::NRO => "https://ftp.ripe.net/pub/stats/ripencc/nro-stats",
+            Registry::Custom { .. } => "",
+        }
+    }
+}
+
+/// Converts a (case-insensitive) registry name to a Registry. RIPE is accepted both as `ripe`
+/// and as `ripencc`, the name it uses in its listings.
+impl FromStr for Registry {
+    type Err = &'static str;
+
+    /// Parses the name of a registry, ignoring case, or the URL template of a custom registry as
+    /// it is displayed. Any value that contains `://` is taken to be a URL template.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let string = value.to_lowercase();
+
+        if value.contains("://") {
+            Ok(Registry::Custom {
+                url_template: value.to_string(),
+            })
+        } else if string.eq("afrinic") {
+            Ok(Registry::AFRINIC)
+        } else if string.eq("apnic") {
+            Ok(Registry::APNIC)
+        } else if string.eq("arin") {
+            Ok(Registry::ARIN)
+        } else if string.eq("lacnic") {
+            Ok(Registry::LACNIC)
+        } else if string.eq("ripe") || string.eq("ripencc") {
+            Ok(Registry::RIPE)
+        } else if string.eq("nro") {
+            Ok(Registry::NRO)
+        } else {
+            Err("Unknown registry not matching 'afrinic', 'apnic', 'arin', 'lacnic', 'ripe', 'nro' or a URL template found while attempting to parse registry.")
+        }
+    }
+}
+
+/// Converts a Registry to the lowercase name it uses in the registry field of its listings.
+/// Custom registries are converted to their URL template. Either form is parsed back to the same
+/// Registry by `FromStr`.
+impl fmt::Display for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Registry::AFRINIC => write!(f, "afrinic"),
+            Registry::APNIC => write!(f, "apnic"),
+            Registry::ARIN => write!(f, "arin"),
+            Registry::LACNIC => write!(f, "lacnic"),
+            Registry::RIPE => write!(f, "ripencc"),
+            Registry::NRO => write!(f, "nro"),
+            Registry::Custom { url_template } => write!(f, "{}", url_template),
+        }
+    }
+}
+
+/// A value the server returned along with a listing, that can be sent back to it to only download
+/// the listing again once it has changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Validator {
+    /// The value of the `ETag` header.
+    ETag(String),
+
+    /// The value of the `Last-Modified` header.
+    LastModified(String),
+}
+
+impl Validator {
+    /// Returns the ETag of a response, or its Last-Modified date if it has no ETag.
+    fn from_headers(headers: &HeaderMap) -> Option<Validator> {
+        header(headers, ETAG)
+            .map(Validator::ETag)
+            .or_else(|| header(headers, LAST_MODIFIED).map(Validator::LastModified))
+    }
+}
+
+/// The outcome of a conditional download.
+pub enum ConditionalDownload {
+    /// The listing changed. Contains a decoded stream of the listing and the validator to pass to
+    /// the next download, if the server returned one.
+    Modified(Box<dyn Read + Send>, Option<Validator>),
+
+    /// The listing did not change since it was downloaded with the given validator.
+    NotModified,
+}
+
+/// Describes the response a listing was downloaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadMeta {
+    /// The URL the listing was downloaded from, after following any redirects.
+    pub url: String,
+
+    /// The size of the (compressed) listing as announced by the server, if it announced it.
+    pub content_length: Option<u64>,
+
+    /// The value of the `Last-Modified` header, if the server returned one.
+    pub last_modified: Option<String>,
+
+    /// The value of the `ETag` header, if the server returned one.
+    pub etag: Option<String>,
+}
+
+/// Configures where the listings of the registries are downloaded from.
+///
+/// # Example
+/// ```
+/// use rsef_rs::{DownloadConfig, Registry};
+///
+/// let config = DownloadConfig::default()
+///     .with_mirror(Registry::RIPE, "https://mirror.example.com/ripencc");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DownloadConfig {
+    /// The base URLs of the mirrors to download the listings of the registries from, e.g.
+    /// `https://mirror.example.com/ripencc`. The path to the listing is appended to the base URL
+    /// in the same way as on the primary mirror of the registry.
+    pub mirrors: HashMap<Registry, String>,
+
+    /// The User-Agent to identify downloads with, or None to use `USER_AGENT`. Some mirrors ask
+    /// tools to identify themselves, and may block requests that do not.
+    pub user_agent: Option<String>,
+}
+
+impl DownloadConfig {
+    /// Downloads the listings of a registry from the mirror found at the given base URL.
+    pub fn with_mirror(mut self, registry: Registry, base_url: impl Into<String>) -> Self {
+        self.mirrors.insert(registry, base_url.into());
+        self
+    }
+
+    /// Identifies downloads with the given User-Agent.
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Returns the URL of the listing of a registry for a specific date.
+    fn url(&self, registry: &Registry, date: NaiveDate, extended: bool) -> String {
+        let base_url = match self.mirrors.get(registry) {
+            Some(base_url) => base_url.as_str(),
+            None => registry.base_url(),
+        };
+
+        registry.url_on(base_url, date, extended)
+    }
+}
+
+/// Downloads the listings of the registries using the same client, mirrors, timeout and retry
+/// policy for every download. Use `Downloader::builder` to configure a Downloader.
+///
+/// # Example
+/// ```no_run
+/// use rsef_rs::{Downloader, Registry, RetryPolicy};
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// let downloader = Downloader::builder()
+///     .timeout(Duration::from_secs(60))
+///     .retries(RetryPolicy::default())
+///     .user_agent("example/1.0 (ops@example.com)")
+///     .build()?;
+///
+/// let date = chrono::NaiveDate::from_ymd_opt(2019, 2, 1).unwrap();
+/// let stream = downloader.download(&Registry::RIPE, date).await?;
+/// let records = rsef_rs::read_all(stream)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Downloader {
+    client: Client,
+    config: DownloadConfig,
+    retry: RetryPolicy,
+}
 
-                let response = reqwest::get(url.as_str())?;
-                Ok(Box::new(response))
+impl Downloader {
+    /// Returns a builder to configure a Downloader. Without any configuration, listings are
+    /// downloaded from the primary mirrors of the registries, without a timeout or retries.
+    pub fn builder() -> DownloaderBuilder {
+        DownloaderBuilder::default()
+    }
+
+    /// Downloads the RSEF listings of a specific registry for a specific date. Returns a decoded
+    /// stream that can be read from.
+    pub async fn download(
+        &self,
+        registry: &Registry,
+        date: NaiveDate,
+    ) -> Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>> {
+        let url = self.config.url(registry, date, true);
+        let mut attempt = 1;
+
+        loop {
+            match registry.fetch_url(&self.client, &url, date).await {
+                Err(err) if attempt < self.retry.max_attempts && is_transient(err.as_ref()) => {
+                    tokio::time::sleep(self.retry.delay(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
             }
-            Registry::LACNIC => {
-                let url = format!(
-                    "https://ftp.lacnic.net/pub/stats/lacnic/delegated-lacnic-extended-{}{}{}",
-                    year, month, day
-                );
+        }
+    }
+
+    /// Downloads and parses the RSEF listings of all the Regional Internet Registries for a
+    /// specific date, see `Registry::download_all`. The timeout and retry policy of this
+    /// Downloader apply to every registry separately.
+    pub async fn download_all(
+        &self,
+        date: NaiveDate,
+    ) -> HashMap<Registry, Result<Vec<crate::Line>, crate::RsefError>> {
+        let downloads = Registry::all().map(|registry| {
+            let date = date.min(registry.latest_date());
+            let download = tokio::spawn({
+                let downloader = self.clone();
+                let registry = registry.clone();
+                async move { downloader.download_lines(&registry, date).await }
+            });
+
+            (registry, download)
+        });
+
+        let mut listings = HashMap::new();
+        for (registry, download) in downloads {
+            let lines = match download.await {
+                Ok(lines) => lines,
+                Err(err) => Err(crate::RsefError::Io(std::io::Error::other(err))),
+            };
+            listings.insert(registry, lines);
+        }
+
+        listings
+    }
+
+    /// Downloads the listing of a registry for a specific date and parses it with
+    /// `tokio::task::spawn_blocking`, as decompressing and parsing a whole listing would block the
+    /// runtime.
+    async fn download_lines(
+        &self,
+        registry: &Registry,
+        date: NaiveDate,
+    ) -> Result<Vec<crate::Line>, crate::RsefError> {
+        let stream = self.download(registry, date).await.map_err(to_rsef_error)?;
+
+        tokio::task::spawn_blocking(move || Ok(crate::read_all(stream)?.collect()))
+            .await
+            .map_err(|err| crate::RsefError::Io(std::io::Error::other(err)))?
+    }
 
-                let response = reqwest::get(url.as_str())?;
-                Ok(Box::new(response))
+    /// Downloads the RSEF listings of a specific registry for every date from start up to and
+    /// including end, see `Registry::download_range`. The timeout and retry policy of this
+    /// Downloader apply to every date separately.
+    pub async fn download_range(
+        &self,
+        registry: &Registry,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Vec<(
+        NaiveDate,
+        Result<Box<dyn Read + Send>, Box<dyn Error + Send + Sync>>,
+    )> {
+        let mut listings = Vec::new();
+
+        for date in start.iter_days().take_while(|date| *date <= end) {
+            match self.download(registry, date).await {
+                Err(err) if is_not_found(err.as_ref()) => continue,
+                result => listings.push((date, result)),
             }
-            Registry::RIPE => {
-                let url = format!(
-                    "https://ftp.ripe.net/pub/stats/ripencc/{}/delegated-ripencc-extended-{}{}{}.bz2",
-                    year, year, month, day
-                );
+        }
+
+        listings
+    }
+}
+
+/// Configures a Downloader, see `Downloader::builder`.
+#[derive(Debug, Clone, Default)]
+pub struct DownloaderBuilder {
+    client: Option<Client>,
+    config: DownloadConfig,
+    retry: Option<RetryPolicy>,
+    timeout: Option<Duration>,
+}
 
-                let response = reqwest::get(url.as_str())?;
-                Ok(Box::new(BzDecoder::new(response)))
+impl DownloaderBuilder {
+    /// Downloads the listings using the given client, so connection pools, proxies and TLS
+    /// settings can be reused. The timeout and User-Agent are not applied to this client.
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Downloads the listings from the mirrors and with the User-Agent of the given config.
+    pub fn config(mut self, config: DownloadConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Downloads the listings of a registry from the mirror found at the given base URL.
+    pub fn mirror(mut self, registry: Registry, base_url: impl Into<String>) -> Self {
+        self.config = self.config.with_mirror(registry, base_url);
+        self
+    }
+
+    /// Retries downloads that fail with a transient error according to the given policy.
+    pub fn retries(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Gives up on a download attempt once the given timeout has elapsed. The timeout applies to
+    /// the whole request, from connecting until the body has been received.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Identifies downloads with the given User-Agent instead of `USER_AGENT`.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.config = self.config.with_user_agent(user_agent);
+        self
+    }
+
+    /// Creates the Downloader. Fails if the HTTP client cannot be created, e.g. because the TLS
+    /// backend cannot be initialized.
+    pub fn build(self) -> Result<Downloader, reqwest::Error> {
+        let client = match self.client {
+            Some(client) => client,
+            None => {
+                let user_agent = self.config.user_agent.as_deref().unwrap_or(USER_AGENT);
+                let mut builder = Client::builder().user_agent(user_agent);
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                builder.build()?
             }
+        };
+
+        Ok(Downloader {
+            client,
+            config: self.config,
+            retry: self.retry.unwrap_or(RetryPolicy {
+                max_attempts: 1,
+                base_delay: Duration::from_secs(0),
+            }),
+        })
+    }
+}
+
+/// Describes how often and how quickly failed downloads are retried.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+
+    /// The delay before the first retry. Every following retry waits twice as long as the previous.
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Returns the delay to wait after the given (1-based) failed attempt.
+    fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .checked_mul(2u32.saturating_pow(attempt - 1))
+            .unwrap_or(Duration::MAX)
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Makes up to 3 attempts, waiting 1 and 2 seconds between them.
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_secs(1),
         }
     }
 }
 
+/// Returns whether the error is a network error or a 5xx response that may succeed when retried.
+fn is_transient(err: &(dyn Error + 'static)) -> bool {
+    if let Some(crate::RsefError::HttpStatus { status, .. }) = err.downcast_ref() {
+        return status.is_server_error();
+    }
+
+    match err.downcast_ref::<reqwest::Error>() {
+        Some(err) => match err.status() {
+            Some(status) => status.is_server_error(),
+            None => err.is_timeout() || err.is_connect() || err.is_request() || err.is_body(),
+        },
+        None => false,
+    }
+}
+
+/// Returns whether the error was caused by the server responding with 404 Not Found.
+fn is_not_found(err: &(dyn Error + 'static)) -> bool {
+    if let Some(crate::RsefError::ListingNotAvailable { .. }) = err.downcast_ref() {
+        return true;
+    }
+
+    err.downcast_ref::<reqwest::Error>()
+        .and_then(|err| err.status())
+        == Some(StatusCode::NOT_FOUND)
+}
+
+/// Converts the error of a download into an `RsefError`. Errors that are neither an `RsefError` nor
+/// a `reqwest::Error` are wrapped in `RsefError::Io`.
+fn to_rsef_error(err: Box<dyn Error + Send + Sync>) -> crate::RsefError {
+    let err = match err.downcast::<crate::RsefError>() {
+        Ok(err) => return *err,
+        Err(err) => err,
+    };
+
+    match err.downcast::<reqwest::Error>() {
+        Ok(err) => crate::RsefError::Http(*err),
+        Err(err) => crate::RsefError::Io(std::io::Error::other(err)),
+    }
+}
+
+/// Returns the value of a header, if it is present and valid ASCII.
+fn header(headers: &HeaderMap, name: HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+}
+
+/// Returns a builder for a client that identifies itself with `USER_AGENT`.
+fn client_builder() -> ClientBuilder {
+    Client::builder().user_agent(USER_AGENT)
+}
+
+/// Creates a client that identifies itself with `USER_AGENT`.
+fn client() -> Result<Client, reqwest::Error> {
+    client_builder().build()
+}
+
+/// Sends a request and logs its URL and the status of the response.
+async fn send(request: RequestBuilder) -> Result<Response, reqwest::Error> {
+    let (client, request) = request.build_split();
+    let request = request?;
+
+    debug!(url = %request.url(), "Sending request");
+    let response = client.execute(request).await?;
+    debug!(status = %response.status(), "Received response");
+
+    Ok(response)
+}
+
+/// Downloads the raw contents found at the given URL using the given client.
+#[cfg(feature = "checksum")]
+async fn fetch_bytes(client: &Client, url: &str) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let response = send(client.get(url)).await?;
+    Ok(response.error_for_status()?.bytes().await?.into())
+}
+
+/// Decodes a downloaded listing. Some mirrors respond to requests for listings that do not exist
+/// with an HTML error page and status 200 instead of 404, which is rejected rather than parsed.
+/// The listing may still be read from the network as it is consumed.
+fn decode_listing<R: Read + Send + 'static>(
+    mut read: R,
+) -> Result<Box<dyn Read + Send>, crate::RsefError> {
+    let mut start = Vec::with_capacity(SNIFF_LEN as usize);
+    read.by_ref().take(SNIFF_LEN).read_to_end(&mut start)?;
+    crate::decode(reject_html(start)?.chain(read))
+}
+
+/// Decodes a listing that is received asynchronously, rejecting HTML error pages like
+/// `decode_listing` does.
+async fn decode_listing_async<R: AsyncRead + Send + Unpin + 'static>(
+    mut read: R,
+) -> Result<Box<dyn AsyncRead + Send + Unpin>, crate::RsefError> {
+    use tokio::io::AsyncReadExt;
+
+    let mut start = Vec::with_capacity(SNIFF_LEN as usize);
+    (&mut read).take(SNIFF_LEN).read_to_end(&mut start).await?;
+    crate::decode_async(AsyncReadExt::chain(reject_html(start)?, read)).await
+}
+
+/// The number of bytes at the start of a downloaded listing that are inspected for an HTML error
+/// page, which leaves room for some whitespace in front of the start of the document.
+const SNIFF_LEN: u64 = 512;
+
+/// Returns `RsefError::HtmlResponse` if the first `SNIFF_LEN` bytes of a downloaded listing are
+/// the start of an HTML error page. Otherwise, as these bytes were consumed from the listing, they
+/// are returned as a stream of their own to be put back in front of the rest of it.
+fn reject_html(start: Vec<u8>) -> Result<Cursor<Vec<u8>>, crate::RsefError> {
+    if is_html(&start) {
+        return Err(crate::RsefError::HtmlResponse);
+    }
+
+    Ok(Cursor::new(start))
+}
+
+/// Returns whether the data starts like an HTML document, ignoring leading whitespace.
+fn is_html(data: &[u8]) -> bool {
+    let start = data
+        .iter()
+        .position(|x| !x.is_ascii_whitespace())
+        .unwrap_or(data.len());
+    let prefix = &data[start..data.len().min(start + 9)];
+
+    prefix.len() >= 5
+        && (prefix.eq_ignore_ascii_case(b"<!doctype") || prefix[..5].eq_ignore_ascii_case(b"<html"))
+}
+
+/// Extracts the md5 digest from a checksum file. Registries publish these both in the format of
+/// `md5sum` (`<digest>  <file>`) and of BSD `md5` (`MD5 (<file>) = <digest>`).
+#[cfg(feature = "checksum")]
+fn parse_md5(checksum: &str) -> Option<String> {
+    checksum
+        .split(|c: char| c.is_whitespace() || c == '=')
+        .find(|token| token.len() == 32 && token.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(|token| token.to_ascii_lowercase())
+}
+
+/// Converts an UNIX Epoch to the UTC date it falls on.
+fn to_date(timestamp: i64) -> Result<NaiveDate, Box<dyn Error + Send + Sync>> {
+    let datetime = DateTime::from_timestamp(timestamp, 0).ok_or("Invalid UNIX timestamp")?;
+    Ok(datetime.date_naive())
+}
+
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use crate::Registry;
+    use crate::{DownloadConfig, Downloader, Registry, RetryPolicy, Validator};
 
-    #[test]
-    fn test_download() {
+    use chrono::NaiveDate;
+    use reqwest::header::{HeaderMap, HeaderValue, ETAG, LAST_MODIFIED};
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    /// A listing for the local mirrors of the tests to serve.
+    const LISTING: &str = "2|ripencc|20190201|1|19830705|20190201|+0100\n\
+                           ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n";
+
+    /// The timestamp of a moment on 1 February 2019.
+    const TIMESTAMP: i64 = 1_549_056_168;
+
+    /// Returns a registry whose listings are downloaded from the local mirror at the given URL.
+    fn local(mirror: &str) -> Registry {
+        Registry::Custom {
+            url_template: format!("{}/delegated-{{date}}", mirror),
+        }
+    }
+
+    /// Compresses the data with gzip, as some registries serve their listings.
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        use std::io::Write;
+
+        let mut encoder = libflate::gzip::Encoder::new(Vec::new()).unwrap();
+        encoder.write_all(data).unwrap();
+        encoder.finish().into_result().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_download() {
         // Friday 1 February 2019 21:22:48
         let timestamp = 1_549_056_168;
 
         println!("Downloading from AFRINIC");
-        let stream = Registry::AFRINIC.download(timestamp).unwrap();
+        let stream = Registry::AFRINIC.download(timestamp).await.unwrap();
         let _ = crate::read_all(stream).unwrap();
 
         println!("Downloading from APNIC");
-        let stream = Registry::APNIC.download(timestamp).unwrap();
+        let stream = Registry::APNIC.download(timestamp).await.unwrap();
         let _ = crate::read_all(stream).unwrap();
 
         println!("Downloading from ARIN");
-        let stream = Registry::ARIN.download(timestamp).unwrap();
+        let stream = Registry::ARIN.download(timestamp).await.unwrap();
         let _ = crate::read_all(stream).unwrap();
 
         println!("Downloading from LACNIC");
-        let stream = Registry::LACNIC.download(timestamp).unwrap();
+        let stream = Registry::LACNIC.download(timestamp).await.unwrap();
         let _ = crate::read_all(stream).unwrap();
 
         println!("Downloading from RIPE");
-        let stream = Registry::RIPE.download(timestamp).unwrap();
+        let stream = Registry::RIPE.download(timestamp).await.unwrap();
         let _ = crate::read_all(stream).unwrap();
     }
+
+    #[test]
+    fn test_url() {
+        let date = NaiveDate::from_ymd_opt(2019, 2, 11).unwrap();
+
+        assert_eq!(
+            Registry::RIPE.url(date, true),
+            "https://ftp.ripe.net/pub/stats/ripencc/2019/delegated-ripencc-extended-20190211.bz2"
+        );
+        assert_eq!(
+            Registry::ARIN.url(date, true),
+            "https://ftp.arin.net/pub/stats/arin/delegated-arin-extended-20190211"
+        );
+        assert_eq!(
+            Registry::APNIC.url(date, false),
+            "https://ftp.apnic.net/stats/apnic/2019/delegated-apnic-20190211.gz"
+        );
+        assert_eq!(
+            Registry::NRO.url(date, true),
+            "https://ftp.ripe.net/pub/stats/ripencc/nro-stats/20190211/nro-delegated-stats"
+        );
+    }
+
+    #[test]
+    fn test_fallback_url() {
+        let date = NaiveDate::from_ymd_opt(2012, 5, 1).unwrap();
+        let url = Registry::AFRINIC.url(date, true);
+
+        assert_eq!(
+            Registry::AFRINIC.fallback_url(&url, date),
+            Some(
+                "https://ftp.afrinic.net/pub/stats/afrinic/delegated-afrinic-extended-20120501"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            Registry::RIPE.fallback_url(&Registry::RIPE.url(date, true), date),
+            None
+        );
+    }
+
+    /// A request received by `serve`.
+    struct Request {
+        path: String,
+        headers: HashMap<String, String>,
+    }
+
+    impl Request {
+        /// Returns the value of a header, whose name should be lowercase.
+        fn header(&self, name: &str) -> Option<&str> {
+            self.headers.get(name).map(String::as_str)
+        }
+    }
+
+    /// Serves HTTP requests on a local port, answering every request with the raw HTTP response
+    /// that `respond` returns for it. Every connection is handled on a thread of its own, so a
+    /// response that stalls does not hold up the others. Returns the base URL of the server.
+    fn serve(respond: fn(&Request) -> Vec<u8>) -> String {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                std::thread::spawn(move || {
+                    let mut lines = BufReader::new(&stream).lines().map(Result::unwrap);
+                    let path = lines.next().unwrap().split(' ').nth(1).unwrap().to_string();
+                    let headers = lines
+                        .take_while(|line| !line.is_empty())
+                        .filter_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            Some((name.to_lowercase(), value.trim().to_string()))
+                        })
+                        .collect();
+
+                    let response = respond(&Request { path, headers });
+                    // The client may have given up on a stalled response already.
+                    let _ = stream.write_all(&response);
+                });
+            }
+        });
+
+        url
+    }
+
+    /// Builds an HTTP response with the given status, headers and body, announcing the length of
+    /// the body.
+    fn response(status: u16, headers: &[(&str, &str)], body: &[u8]) -> Vec<u8> {
+        let mut response = format!("HTTP/1.1 {} Status\r\n", status);
+        for (name, value) in headers {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        response.push_str(&format!(
+            "Content-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        ));
+
+        let mut response = response.into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    #[tokio::test]
+    async fn test_download_all() {
+        let mirror = serve(|request| {
+            if request.header("user-agent") != Some(super::USER_AGENT) {
+                return response(400, &[], b"");
+            }
+
+            match request.path.as_str() {
+                "/delegated-arin-extended-20190201" => response(503, &[], b""),
+                _ => response(200, &[], LISTING.as_bytes()),
+            }
+        });
+        let downloader = Registry::all()
+            .iter()
+            .fold(Downloader::builder(), |builder, registry| {
+                builder.mirror(registry.clone(), mirror.as_str())
+            })
+            .build()
+            .unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2019, 2, 1).unwrap();
+        let mut listings = downloader.download_all(date).await;
+        assert_eq!(listings.len(), Registry::all().len());
+
+        assert!(matches!(
+            listings.remove(&Registry::ARIN),
+            Some(Err(crate::RsefError::HttpStatus { .. }))
+        ));
+        for (_, lines) in listings {
+            assert_eq!(lines.unwrap().len(), 2);
+        }
+    }
+
+    /// Continues the download of `LISTING` from a local mirror that answers with `respond`, after
+    /// the given number of bytes was written to a file already. Returns the offset the remainder
+    /// starts at and the contents of the file once the remainder has been written at that offset.
+    async fn resume(respond: fn(&Request) -> Vec<u8>, written: usize) -> (u64, Vec<u8>) {
+        use std::io::Read;
+
+        let mut file = LISTING.as_bytes()[..written].to_vec();
+        let (mut remainder, start) = local(&serve(respond))
+            .download_resume(TIMESTAMP, written as u64)
+            .await
+            .unwrap();
+
+        file.truncate(start as usize);
+        remainder.read_to_end(&mut file).unwrap();
+        (start, file)
+    }
+
+    #[tokio::test]
+    async fn test_download_resume() {
+        let honors_range = |request: &Request| {
+            let offset = request
+                .header("range")
+                .and_then(|range| range.strip_prefix("bytes="))
+                .and_then(|range| range.strip_suffix('-'))
+                .and_then(|offset| offset.parse::<usize>().ok());
+
+            match offset {
+                Some(offset) => response(206, &[], &LISTING.as_bytes()[offset..]),
+                None => response(400, &[], b""),
+            }
+        };
+        assert_eq!(resume(honors_range, 10).await, (10, LISTING.into()));
+
+        // A server that does not support ranges returns the whole listing, overwriting the file.
+        let ignores_range = |_: &Request| response(200, &[], LISTING.as_bytes());
+        assert_eq!(resume(ignores_range, 10).await, (0, LISTING.into()));
+
+        // The whole listing was written already, so the file is left as it is.
+        let complete = |_: &Request| response(416, &[], b"");
+        assert_eq!(
+            resume(complete, LISTING.len()).await,
+            (LISTING.len() as u64, LISTING.into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_with_timeout() {
+        let stalls = |_: &Request| {
+            std::thread::sleep(Duration::from_secs(5));
+            response(200, &[], LISTING.as_bytes())
+        };
+        let registry = local(&serve(stalls));
+
+        let started = std::time::Instant::now();
+        let err = registry
+            .download_with_timeout(TIMESTAMP, Duration::from_millis(200))
+            .await
+            .err()
+            .unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(err.downcast_ref::<reqwest::Error>().unwrap().is_timeout());
+    }
+
+    /// Downloads `LISTING` from a local mirror that answers with `respond`, returning every
+    /// progress report in the order they were made.
+    async fn progress(respond: fn(&Request) -> Vec<u8>) -> Vec<(u64, Option<u64>)> {
+        let mut reports = Vec::new();
+        local(&serve(respond))
+            .download_with_progress(TIMESTAMP, |received, total| reports.push((received, total)))
+            .await
+            .unwrap();
+
+        assert!(reports.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(reports.last().unwrap().0, LISTING.len() as u64);
+        reports
+    }
+
+    #[tokio::test]
+    async fn test_download_with_progress() {
+        let reports = progress(|_| response(200, &[], LISTING.as_bytes())).await;
+        let total = Some(LISTING.len() as u64);
+        assert!(reports.iter().all(|report| report.1 == total));
+
+        // Without a Content-Length, the total size is not known.
+        let chunked = |_: &Request| {
+            let mut response =
+                b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n"
+                    .to_vec();
+            for chunk in LISTING.as_bytes().chunks(16) {
+                response.extend(format!("{:x}\r\n", chunk.len()).bytes());
+                response.extend(chunk);
+                response.extend(b"\r\n");
+            }
+            response.extend(b"0\r\n\r\n");
+            response
+        };
+        let reports = progress(chunked).await;
+        assert!(reports.iter().all(|report| report.1.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_download_if_modified() {
+        use crate::ConditionalDownload;
+
+        let registry = local(&serve(|request| {
+            let validators = (
+                request.header("if-none-match"),
+                request.header("if-modified-since"),
+            );
+
+            match validators {
+                (Some("\"v1\""), None) => response(304, &[], b""),
+                (None, Some("Fri, 01 Feb 2019 00:00:00 GMT")) => response(304, &[], b""),
+                (None, None) => response(200, &[("ETag", "\"v2\"")], LISTING.as_bytes()),
+                _ => response(400, &[], b""),
+            }
+        }));
+
+        let etag = Validator::ETag("\"v1\"".to_string());
+        assert!(matches!(
+            registry.download_if_modified(TIMESTAMP, Some(&etag)).await,
+            Ok(ConditionalDownload::NotModified)
+        ));
+
+        let date = Validator::LastModified("Fri, 01 Feb 2019 00:00:00 GMT".to_string());
+        assert!(matches!(
+            registry.download_if_modified(TIMESTAMP, Some(&date)).await,
+            Ok(ConditionalDownload::NotModified)
+        ));
+
+        match registry.download_if_modified(TIMESTAMP, None).await {
+            Ok(ConditionalDownload::Modified(stream, validator)) => {
+                assert_eq!(validator, Some(Validator::ETag("\"v2\"".to_string())));
+                assert_eq!(crate::read_all(stream).unwrap().count(), 2);
+            }
+            _ => panic!("Expected a modified listing"),
+        }
+    }
+
+    #[cfg(feature = "checksum")]
+    #[tokio::test]
+    async fn test_download_verified() {
+        use std::io::Read;
+
+        let mirror = serve(|request| match request.path.as_str() {
+            "/valid-20190201" | "/corrupt-20190201" => response(200, &[], LISTING.as_bytes()),
+            "/valid-20190201.md5" => {
+                let checksum = format!("{:x}  valid-20190201\n", md5::compute(LISTING));
+                response(200, &[], checksum.as_bytes())
+            }
+            "/corrupt-20190201.md5" => {
+                let checksum = format!("{:x}  corrupt-20190201\n", md5::compute("corrupt"));
+                response(200, &[], checksum.as_bytes())
+            }
+            _ => response(404, &[], b""),
+        });
+        let registry = |name: &str| Registry::Custom {
+            url_template: format!("{}/{}-{{date}}", mirror, name),
+        };
+
+        let mut listing = String::new();
+        registry("valid")
+            .download_verified(TIMESTAMP)
+            .await
+            .unwrap()
+            .read_to_string(&mut listing)
+            .unwrap();
+        assert_eq!(listing, LISTING);
+
+        let err = registry("corrupt")
+            .download_verified(TIMESTAMP)
+            .await
+            .err()
+            .unwrap();
+        match err.downcast_ref::<crate::RsefError>() {
+            Some(crate::RsefError::Checksum { expected, actual }) => {
+                assert_eq!(*expected, format!("{:x}", md5::compute("corrupt")));
+                assert_eq!(*actual, format!("{:x}", md5::compute(LISTING)));
+            }
+            _ => panic!("Expected a checksum error, got {}", err),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_with_meta() {
+        let mirror = serve(|_| {
+            let headers = [
+                ("Last-Modified", "Fri, 01 Feb 2019 00:00:00 GMT"),
+                ("ETag", "\"v1\""),
+            ];
+            response(200, &headers, LISTING.as_bytes())
+        });
+
+        let (stream, meta) = local(&mirror).download_with_meta(TIMESTAMP).await.unwrap();
+        assert_eq!(crate::read_all(stream).unwrap().count(), 2);
+        assert_eq!(
+            meta,
+            crate::DownloadMeta {
+                url: format!("{}/delegated-20190201", mirror),
+                content_length: Some(LISTING.len() as u64),
+                last_modified: Some("Fri, 01 Feb 2019 00:00:00 GMT".to_string()),
+                etag: Some("\"v1\"".to_string()),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_raw() {
+        let registry = local(&serve(|_| response(200, &[], &gzip(LISTING.as_bytes()))));
+
+        let raw = registry.download_raw(TIMESTAMP).await.unwrap();
+        assert!(raw.starts_with(&[0x1f, 0x8b]));
+        assert_eq!(raw, gzip(LISTING.as_bytes()));
+    }
+
+    #[tokio::test]
+    async fn test_download_bytes() {
+        let registry = local(&serve(|_| response(200, &[], &gzip(LISTING.as_bytes()))));
+
+        assert_eq!(
+            registry.download_bytes(TIMESTAMP).await.unwrap(),
+            LISTING.as_bytes()
+        );
+        assert_eq!(registry.download_string(TIMESTAMP).await.unwrap(), LISTING);
+
+        let registry = local(&serve(|_| response(200, &[], &gzip(b"ripencc|\xff\n"))));
+        let err = registry.download_string(TIMESTAMP).await.err().unwrap();
+        assert!(err.downcast_ref::<std::string::FromUtf8Error>().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_download_async_reader() {
+        let listing = LISTING.repeat(1000);
+        let registry = local(&serve(|_| {
+            response(200, &[], &gzip(LISTING.repeat(1000).as_bytes()))
+        }));
+
+        let stream = registry.download_async_reader(TIMESTAMP).await.unwrap();
+        let lines = crate::read_all_async(stream).await.unwrap();
+        assert_eq!(lines, crate::parse_str(&listing).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_download_fallback() {
+        let mirror = serve(|request| match request.path.as_str() {
+            "/delegated-afrinic-extended-20120501" => response(
+                200,
+                &[],
+                b"afrinic|ZA|asn|1228|1|19910301|allocated|F36B9F4B\n",
+            ),
+            _ => response(404, &[], b""),
+        });
+        let downloader = Downloader::builder()
+            .mirror(Registry::AFRINIC, mirror)
+            .build()
+            .unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2012, 5, 1).unwrap();
+        let stream = downloader.download(&Registry::AFRINIC, date).await.unwrap();
+        assert_eq!(crate::read_all(stream).unwrap().count(), 1);
+
+        let date = NaiveDate::from_ymd_opt(2012, 5, 2).unwrap();
+        let err = downloader
+            .download(&Registry::AFRINIC, date)
+            .await
+            .err()
+            .unwrap();
+        assert!(super::is_not_found(err.as_ref()));
+    }
+
+    #[test]
+    fn test_custom_url() {
+        let date = NaiveDate::from_ymd_opt(2019, 2, 1).unwrap();
+        let registry = Registry::Custom {
+            url_template: "https://ftp.example.jp/{year}/{month}/{day}/delegated-jpnic-{date}"
+                .to_string(),
+        };
+
+        assert_eq!(
+            registry.url(date, true),
+            "https://ftp.example.jp/2019/02/01/delegated-jpnic-20190201"
+        );
+    }
+
+    #[test]
+    fn test_mirror_url() {
+        let date = NaiveDate::from_ymd_opt(2019, 2, 11).unwrap();
+        let config =
+            DownloadConfig::default().with_mirror(Registry::RIPE, "http://mirror.local/ripe/");
+
+        assert_eq!(
+            config.url(&Registry::RIPE, date, true),
+            "http://mirror.local/ripe/2019/delegated-ripencc-extended-20190211.bz2"
+        );
+        assert_eq!(
+            config.url(&Registry::ARIN, date, true),
+            Registry::ARIN.url(date, true)
+        );
+    }
+
+    #[test]
+    fn test_downloader_builder() {
+        let downloader = Downloader::builder()
+            .mirror(Registry::RIPE, "http://mirror.local/ripe")
+            .retries(RetryPolicy::default())
+            .user_agent("example/1.0")
+            .build()
+            .unwrap();
+
+        assert_eq!(downloader.retry.max_attempts, 3);
+        assert_eq!(downloader.config.user_agent.as_deref(), Some("example/1.0"));
+        assert!(downloader.config.mirrors.contains_key(&Registry::RIPE));
+
+        let downloader = Downloader::builder().build().unwrap();
+        assert_eq!(downloader.retry.max_attempts, 1);
+    }
+
+    #[test]
+    fn test_to_rsef_error() {
+        let err = crate::RsefError::HtmlResponse.into();
+        assert!(matches!(
+            super::to_rsef_error(err),
+            crate::RsefError::HtmlResponse
+        ));
+
+        let err = "Invalid date".into();
+        match super::to_rsef_error(err) {
+            crate::RsefError::Io(err) => assert_eq!(err.to_string(), "Invalid date"),
+            err => panic!("Expected an I/O error, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_download_is_send() {
+        fn assert_send<T: Send>(_: T) {}
+
+        assert_send(Registry::RIPE.download(0));
+        assert_send(Registry::AFRINIC.download_raw(0));
+        assert_send(Registry::RIPE.download_async_reader(0));
+        assert_send(Registry::APNIC.download_with_progress(0, |_, _| {}));
+        assert_send(Registry::ARIN.download_if_modified(0, None));
+        assert_send(Registry::LACNIC.download_resume(0, 0));
+
+        let downloader = Downloader::builder().build().unwrap();
+        let date = NaiveDate::from_ymd_opt(2019, 2, 11).unwrap();
+        assert_send(downloader.download(&Registry::RIPE, date));
+    }
+
+    #[tokio::test]
+    async fn test_download_cancellable() {
+        let result = Registry::RIPE
+            .download_cancellable(1_549_056_168, std::future::ready(()))
+            .await;
+
+        match result {
+            Err(err) => assert!(matches!(
+                err.downcast_ref(),
+                Some(crate::RsefError::Cancelled)
+            )),
+            Ok(_) => panic!("Expected the download to be cancelled"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_range_errors() {
+        // Nothing listens on the discard port, so every download fails to connect.
+        let downloader = Downloader::builder()
+            .mirror(Registry::RIPE, "http://127.0.0.1:9")
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap();
+
+        let start = NaiveDate::from_ymd_opt(2019, 2, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2019, 2, 3).unwrap();
+        let listings = downloader.download_range(&Registry::RIPE, start, end).await;
+
+        let dates = listings.iter().map(|(date, _)| *date).collect::<Vec<_>>();
+        assert_eq!(dates, start.iter_days().take(3).collect::<Vec<_>>());
+        assert!(listings.iter().all(|(_, result)| result.is_err()));
+
+        assert!(downloader
+            .download_range(&Registry::RIPE, end, start)
+            .await
+            .is_empty());
+    }
+
+    #[test]
+    fn test_user_agent() {
+        assert!(super::USER_AGENT.starts_with("rsef-rs/"));
+        assert_eq!(DownloadConfig::default().user_agent, None);
+
+        let config = DownloadConfig::default().with_user_agent("example/1.0 (ops@example.com)");
+        assert_eq!(
+            config.user_agent.as_deref(),
+            Some("example/1.0 (ops@example.com)")
+        );
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(100),
+        };
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(400));
+    }
+
+    #[test]
+    #[cfg(feature = "checksum")]
+    fn test_parse_md5() {
+        let digest = "d41d8cd98f00b204e9800998ecf8427e";
+
+        assert_eq!(
+            super::parse_md5(&format!("{}  delegated-arin-extended-20190211\n", digest)),
+            Some(digest.to_string())
+        );
+        assert_eq!(
+            super::parse_md5(&format!(
+                "MD5 (delegated-ripencc-extended-20190211.bz2) = {}",
+                digest
+            )),
+            Some(digest.to_string())
+        );
+        assert_eq!(super::parse_md5("<html>Not Found</html>"), None);
+    }
+
+    #[test]
+    fn test_validator_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(Validator::from_headers(&headers), None);
+
+        let date = "Mon, 11 Feb 2019 23:12:48 GMT";
+        headers.insert(LAST_MODIFIED, HeaderValue::from_static(date));
+        assert_eq!(
+            Validator::from_headers(&headers),
+            Some(Validator::LastModified(date.to_string()))
+        );
+
+        headers.insert(ETAG, HeaderValue::from_static("\"5c61fe30-b1c3e\""));
+        assert_eq!(
+            Validator::from_headers(&headers),
+            Some(Validator::ETag("\"5c61fe30-b1c3e\"".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_listing_not_available() {
+        let date = NaiveDate::from_ymd_opt(2019, 2, 1).unwrap();
+        let err: Box<dyn std::error::Error> = crate::RsefError::ListingNotAvailable {
+            registry: Registry::ARIN,
+            date,
+        }
+        .into();
+
+        assert!(super::is_not_found(err.as_ref()));
+        assert!(!super::is_transient(err.as_ref()));
+        assert_eq!(
+            err.to_string(),
+            "The listing of arin for 2019-02-01 is not available"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_gzip_as_bzip2() {
+        // Some mirrors serve the RIPE NCC listings gzip compressed at the .bz2 URL.
+        let mirror = serve(|request| {
+            if request.path != "/2019/delegated-ripencc-extended-20190201.bz2" {
+                return response(404, &[], b"");
+            }
+
+            response(200, &[], &gzip(LISTING.as_bytes()))
+        });
+        let downloader = Downloader::builder()
+            .mirror(Registry::RIPE, mirror)
+            .build()
+            .unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2019, 2, 1).unwrap();
+        let stream = downloader.download(&Registry::RIPE, date).await.unwrap();
+        assert_eq!(crate::read_all(stream).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_html_response() {
+        let page = "\n<!DOCTYPE html>\n<html><body>404 Not Found</body></html>\n";
+        assert!(matches!(
+            super::decode_listing(page.as_bytes()),
+            Err(crate::RsefError::HtmlResponse)
+        ));
+        assert!(super::is_html(b"<HTML><BODY>Not Found</BODY></HTML>"));
+
+        assert!(!super::is_html(b"<htm"));
+        assert!(!super::is_html(b""));
+
+        // Listings longer than the inspected start are read in full.
+        let listing = "2|ripencc|1|0|19830705|20190201|+0100\n".repeat(100);
+        let mut decoded = String::new();
+        super::decode_listing(std::io::Cursor::new(listing.clone()))
+            .unwrap()
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, listing);
+    }
+
+    #[tokio::test]
+    async fn test_html_response_async() {
+        use std::io::Cursor;
+        use tokio::io::AsyncReadExt;
+
+        let page = "\n<!DOCTYPE html>\n<html><body>404 Not Found</body></html>\n";
+        assert!(matches!(
+            super::decode_listing_async(Cursor::new(page)).await,
+            Err(crate::RsefError::HtmlResponse)
+        ));
+
+        let listing = "2|ripencc|1|0|19830705|20190201|+0100\n".repeat(100);
+        let mut decoded = String::new();
+        super::decode_listing_async(Cursor::new(listing.clone()))
+            .await
+            .unwrap()
+            .read_to_string(&mut decoded)
+            .await
+            .unwrap();
+        assert_eq!(decoded, listing);
+    }
+
+    #[test]
+    fn test_http_status() {
+        let err: Box<dyn std::error::Error> = crate::RsefError::HttpStatus {
+            status: reqwest::StatusCode::SERVICE_UNAVAILABLE,
+            url: "https://ftp.arin.net/pub/stats/arin/delegated-arin-extended-20190201".to_string(),
+        }
+        .into();
+
+        assert!(super::is_transient(err.as_ref()));
+        assert!(!super::is_not_found(err.as_ref()));
+        assert_eq!(
+            err.to_string(),
+            "The server responded with 503 Service Unavailable to \
+             https://ftp.arin.net/pub/stats/arin/delegated-arin-extended-20190201"
+        );
+
+        let err: Box<dyn std::error::Error> = crate::RsefError::HttpStatus {
+            status: reqwest::StatusCode::FORBIDDEN,
+            url: String::new(),
+        }
+        .into();
+        assert!(!super::is_transient(err.as_ref()));
+    }
+
+    #[test]
+    fn test_registry_from_str() {
+        for registry in &["afrinic", "apnic", "arin", "lacnic", "ripencc", "nro"] {
+            let parsed = registry.parse::<Registry>().unwrap();
+            assert_eq!(parsed.to_string(), *registry);
+        }
+
+        let custom = Registry::Custom {
+            url_template: "https://ftp.example.jp/{year}/Delegated-{date}".to_string(),
+        };
+        for registry in Registry::all().iter().chain(&[Registry::NRO, custom]) {
+            assert_eq!(registry.to_string().parse(), Ok(registry.clone()));
+        }
+
+        assert!(matches!("RIPE".parse::<Registry>(), Ok(Registry::RIPE)));
+        assert!(matches!("ARIN".parse::<Registry>(), Ok(Registry::ARIN)));
+        assert!("iana".parse::<Registry>().is_err());
+    }
 }
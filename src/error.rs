@@ -0,0 +1,244 @@
+//!
+//! Errors that can occur while reading RSEF listings.
+//!
+
+use crate::Type;
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Represents an error that occurred while reading an RSEF listing.
+#[derive(Debug)]
+pub enum RsefError {
+    /// An I/O error occurred while reading from the underlying stream.
+    Io(io::Error),
+
+    /// A line in the listing could not be parsed.
+    Parse(ParseError),
+
+    /// The listing does not start with a version line.
+    MissingVersion,
+
+    /// A downloaded listing does not match the md5 checksum published by the registry.
+    #[cfg(feature = "checksum")]
+    Checksum {
+        /// The md5 digest published by the registry.
+        expected: String,
+
+        /// The md5 digest of the downloaded listing.
+        actual: String,
+    },
+
+    /// The registry has not published a listing for the date, e.g. because it is not available
+    /// yet. Downloading the listing of an earlier date may succeed.
+    #[cfg(feature = "download")]
+    ListingNotAvailable {
+        /// The registry the listing was downloaded from.
+        registry: crate::Registry,
+
+        /// The date of the listing.
+        date: chrono::NaiveDate,
+    },
+
+    /// The server responded to the download of a listing with an HTTP error status, other than
+    /// 404 Not Found which results in `ListingNotAvailable`.
+    #[cfg(feature = "download")]
+    HttpStatus {
+        /// The status of the response.
+        status: reqwest::StatusCode,
+
+        /// The URL that was requested.
+        url: String,
+    },
+
+    /// The listing could not be downloaded, e.g. because the server could not be reached or the
+    /// connection was interrupted.
+    #[cfg(feature = "download")]
+    Http(reqwest::Error),
+
+    /// The server returned an HTML page instead of a listing, which some mirrors do instead of
+    /// returning 404 Not Found for listings that do not exist.
+    #[cfg(feature = "download")]
+    HtmlResponse,
+
+    /// The download was cancelled before it completed, see `Registry::download_cancellable`.
+    #[cfg(feature = "download")]
+    Cancelled,
+}
+
+impl fmt::Display for RsefError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RsefError::Io(err) => write!(f, "I/O error while reading RSEF listing: {}", err),
+            RsefError::Parse(err) => err.fmt(f),
+            RsefError::MissingVersion => {
+                write!(f, "The listing does not start with a version line")
+            }
+            #[cfg(feature = "checksum")]
+            RsefError::Checksum { expected, actual } => write!(
+                f,
+                "The listing has md5 digest {} but the registry published {}",
+                actual, expected
+            ),
+            #[cfg(feature = "download")]
+            RsefError::ListingNotAvailable { registry, date } => write!(
+                f,
+                "The listing of {} for {} is not available",
+                registry, date
+            ),
+            #[cfg(feature = "download")]
+            RsefError::HttpStatus { status, url } => {
+                write!(f, "The server responded with {} to {}", status, url)
+            }
+            #[cfg(feature = "download")]
+            RsefError::Http(err) => write!(f, "Failed to download the listing: {}", err),
+            #[cfg(feature = "download")]
+            RsefError::HtmlResponse => {
+                write!(f, "The server returned an HTML page instead of a listing")
+            }
+            #[cfg(feature = "download")]
+            RsefError::Cancelled => write!(f, "The download was cancelled"),
+        }
+    }
+}
+
+impl Error for RsefError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RsefError::Io(err) => Some(err),
+            RsefError::Parse(err) => Some(err),
+            RsefError::MissingVersion => None,
+            #[cfg(feature = "checksum")]
+            RsefError::Checksum { .. } => None,
+            #[cfg(feature = "download")]
+            RsefError::ListingNotAvailable { .. } => None,
+            #[cfg(feature = "download")]
+            RsefError::HttpStatus { .. } => None,
+            #[cfg(feature = "download")]
+            RsefError::Http(err) => Some(err),
+            #[cfg(feature = "download")]
+            RsefError::HtmlResponse => None,
+            #[cfg(feature = "download")]
+            RsefError::Cancelled => None,
+        }
+    }
+}
+
+impl From<io::Error> for RsefError {
+    fn from(err: io::Error) -> Self {
+        RsefError::Io(err)
+    }
+}
+
+#[cfg(feature = "download")]
+impl From<reqwest::Error> for RsefError {
+    fn from(err: reqwest::Error) -> Self {
+        RsefError::Http(err)
+    }
+}
+
+impl From<ParseError> for RsefError {
+    fn from(err: ParseError) -> Self {
+        RsefError::Parse(err)
+    }
+}
+
+/// Describes a line of an RSEF listing that could not be parsed.
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    /// The number of the offending line, starting at 1.
+    pub line: usize,
+
+    /// The raw text of the offending line.
+    pub text: String,
+
+    /// The name of the field that could not be parsed.
+    pub field: &'static str,
+
+    /// A description of what went wrong.
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Failed to parse field '{}' on line {}: {} (line: '{}')",
+            self.field, self.line, self.message, self.text
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// Describes a line of an RSEF listing that could not be read, as collected by
+/// `read_all_collect_errors`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineError {
+    /// The number of the offending line, starting at 1.
+    pub line: usize,
+
+    /// A description of what went wrong.
+    pub message: String,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for LineError {}
+
+/// Describes an inconsistency between the declared and the actual contents of an RSEF listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The listing does not contain a version line.
+    MissingVersion,
+
+    /// The number of records differs from the number declared in the version line.
+    RecordCount {
+        /// The number of records declared in the version line.
+        expected: u32,
+
+        /// The number of records found in the listing.
+        actual: usize,
+    },
+
+    /// The number of records of a resource type differs from the count in its summary line.
+    SummaryCount {
+        /// The resource type of the summary line.
+        res_type: Type,
+
+        /// The number of records declared in the summary line.
+        expected: u32,
+
+        /// The number of records of this resource type found in the listing.
+        actual: usize,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::MissingVersion => write!(f, "The listing has no version line"),
+            ValidationError::RecordCount { expected, actual } => write!(
+                f,
+                "The version line declares {} records but {} were found",
+                expected, actual
+            ),
+            ValidationError::SummaryCount {
+                res_type,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "The {} summary line declares {} records but {} were found",
+                res_type, expected, actual
+            ),
+        }
+    }
+}
+
+impl Error for ValidationError {}
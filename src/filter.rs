@@ -0,0 +1,168 @@
+//!
+//! Helpers to select specific entries from parsed RSEF listings.
+//!
+
+use crate::{Line, Record, Status, Type};
+
+use std::collections::HashMap;
+
+///
+/// Returns an iterator over all the records found in the given lines, skipping version and
+/// summary lines.
+///
+pub fn records(lines: &[Line]) -> impl Iterator<Item = &Record> {
+    lines.iter().filter_map(|line| match line {
+        Line::Record(record) => Some(record),
+        _ => None,
+    })
+}
+
+///
+/// Consumes the given lines and returns an iterator over the records found in them, skipping
+/// version and summary lines. Unlike `records`, the records are moved out rather than borrowed.
+///
+/// # Examples
+///
+/// ```
+/// use rsef_rs::Record;
+///
+/// let listing = "2|ripencc|1|1|19830705|20190201|+0100\n\
+///                ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n";
+/// let lines = rsef_rs::read_all(listing.as_bytes()).unwrap().collect::<Vec<_>>();
+///
+/// let records = rsef_rs::into_records(lines).collect::<Vec<Record>>();
+/// assert_eq!(records.len(), 1);
+/// assert_eq!(records[0].start, "193.0.0.0");
+/// ```
+///
+pub fn into_records(lines: Vec<Line>) -> impl Iterator<Item = Record> {
+    lines.into_iter().filter_map(|line| match line {
+        Line::Record(record) => Some(record),
+        _ => None,
+    })
+}
+
+///
+/// Returns an iterator over all the records of a specific resource type found in the given lines.
+///
+/// # Examples
+///
+/// ```
+/// use rsef_rs::Type;
+///
+/// let listing = "ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+///                ripencc|NL|asn|3333|1|19930901|assigned|abc\n";
+/// let lines = rsef_rs::read_all(listing.as_bytes()).unwrap().collect::<Vec<_>>();
+///
+/// assert_eq!(rsef_rs::records_of_type(&lines, Type::ASN).count(), 1);
+/// ```
+///
+pub fn records_of_type(lines: &[Line], res_type: Type) -> impl Iterator<Item = &Record> {
+    records(lines).filter(move |record| record.res_type == res_type)
+}
+
+///
+/// Returns an iterator over all the records with a specific status found in the given lines.
+/// Statuses are compared case-insensitively, which also applies to `Status::Other`.
+///
+/// # Examples
+///
+/// ```
+/// use rsef_rs::Status;
+///
+/// let listing = "ripencc|NL|ipv4|193.0.0.0|256|19930901|allocated|abc\n\
+///                ripencc|ZZ|ipv4|193.0.4.0|256||available|\n\
+///                arin|US|ipv4|8.8.8.0|256|19921201|LEGACY|def\n";
+/// let lines = rsef_rs::read_all(listing.as_bytes()).unwrap().collect::<Vec<_>>();
+///
+/// assert_eq!(rsef_rs::records_by_status(&lines, Status::Allocated).count(), 1);
+/// assert_eq!(rsef_rs::records_by_status(&lines, Status::from("legacy")).count(), 1);
+/// ```
+///
+pub fn records_by_status(lines: &[Line], status: Status) -> impl Iterator<Item = &Record> {
+    records(lines)
+        .filter(move |record| record.status.as_str().eq_ignore_ascii_case(status.as_str()))
+}
+
+///
+/// Groups the records found in the given lines by the registry they belong to, e.g. to separate
+/// the records of a combined listing. Pseudo-registries such as `iana` and `nro` that appear in
+/// combined listings are kept as groups of their own. Records keep their order within a group.
+///
+/// # Examples
+///
+/// ```
+/// let listing = "ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+///                arin|US|asn|701|1|19900803|assigned|def\n\
+///                iana|ZZ|ipv4|0.0.0.0|16777216|19810901|reserved|iana\n\
+///                ripencc|NL|asn|3333|1|19930901|assigned|abc\n";
+/// let lines = rsef_rs::read_all(listing.as_bytes()).unwrap().collect::<Vec<_>>();
+///
+/// let registries = rsef_rs::group_by_registry(&lines);
+/// assert_eq!(registries.len(), 3);
+/// assert_eq!(registries["ripencc"].len(), 2);
+/// assert_eq!(registries["iana"][0].start, "0.0.0.0");
+/// ```
+///
+pub fn group_by_registry(lines: &[Line]) -> HashMap<String, Vec<&Record>> {
+    let mut registries: HashMap<String, Vec<&Record>> = HashMap::new();
+
+    for record in records(lines) {
+        registries
+            .entry(record.registry.clone())
+            .or_default()
+            .push(record);
+    }
+
+    registries
+}
+
+///
+/// Splits the given lines into the listings they consist of, e.g. when several listings have been
+/// concatenated into a single stream. Every listing starts at a version line and runs up to the
+/// next one. Any lines before the first version line form a listing of their own.
+///
+/// # Examples
+///
+/// ```
+/// use rsef_rs::Line;
+///
+/// let listing = "2|ripencc|1|1|19830705|20190201|+0100\n\
+///                ripencc|*|ipv4|*|1|summary\n\
+///                ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+///                2|arin|1|2|19830705|20190201|-0500\n\
+///                arin|*|asn|*|2|summary\n\
+///                arin|US|asn|701|1|19900803|assigned|def\n\
+///                arin|US|asn|702|1|19900803|assigned|def\n";
+/// let lines = rsef_rs::read_all(listing.as_bytes()).unwrap().collect::<Vec<_>>();
+///
+/// let listings = rsef_rs::split_by_version(&lines);
+/// assert_eq!(listings.len(), 2);
+/// assert_eq!(listings[0].len(), 3);
+/// assert_eq!(listings[1].len(), 4);
+///
+/// for listing in listings {
+///     assert!(matches!(listing[0], Line::Version(_)));
+///     assert_eq!(rsef_rs::validate(listing), Ok(()));
+/// }
+/// ```
+///
+pub fn split_by_version(lines: &[Line]) -> Vec<&[Line]> {
+    let mut listings = Vec::new();
+    let mut start = 0;
+
+    for (index, line) in lines.iter().enumerate() {
+        if let Line::Version(_) = line {
+            if index > start {
+                listings.push(&lines[start..index]);
+            }
+            start = index;
+        }
+    }
+
+    if start < lines.len() {
+        listings.push(&lines[start..]);
+    }
+
+    listings
+}
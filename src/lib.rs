@@ -5,10 +5,56 @@
 //!
 
 use std::convert::TryFrom;
-use std::error::Error;
+use std::fmt;
+use std::fs::File;
 use std::io::BufRead;
 use std::io::BufReader;
 use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncBufReadExt, AsyncRead};
+
+#[macro_use]
+mod macros;
+
+mod address;
+pub mod decode;
+pub mod diff;
+pub mod error;
+pub mod filter;
+pub mod lookup;
+pub mod merge;
+pub mod query;
+pub mod sort;
+pub mod stats;
+pub mod validate;
+pub mod writer;
+
+pub use crate::decode::*;
+pub use crate::diff::*;
+pub use crate::error::*;
+pub use crate::filter::*;
+pub use crate::lookup::*;
+pub use crate::merge::*;
+pub use crate::query::*;
+pub use crate::sort::*;
+pub use crate::stats::*;
+pub use crate::validate::*;
+pub use crate::writer::*;
+
+#[cfg(feature = "csv")]
+pub mod csv;
+
+#[cfg(feature = "csv")]
+pub use crate::csv::*;
+
+#[cfg(feature = "iso3166")]
+pub mod country;
+
+#[cfg(feature = "iso3166")]
+pub use crate::country::*;
 
 #[cfg(feature = "download")]
 pub mod download;
@@ -17,7 +63,8 @@ pub mod download;
 pub use crate::download::*;
 
 /// Represents either a Version, Summary or Record line.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Line {
     /// Represents a version line in an RSEF listing.
     Version(Version),
@@ -30,7 +77,8 @@ pub enum Line {
 }
 
 /// Represents the different number of Internet resource types.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Type {
     /// Autonomous System Number
     ASN,
@@ -64,8 +112,79 @@ impl TryFrom<&str> for Type {
     }
 }
 
+/// Converts a Type to the lowercase string used for it in RSEF listings.
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::ASN => write!(f, "asn"),
+            Type::IPv4 => write!(f, "ipv4"),
+            Type::IPv6 => write!(f, "ipv6"),
+            Type::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Represents the status of an allocation or assignment of an Internet resource.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Status {
+    /// The resource has been allocated to an LIR or NIR.
+    Allocated,
+
+    /// The resource has been assigned to an end user.
+    Assigned,
+
+    /// The resource is reserved by the registry.
+    Reserved,
+
+    /// The resource is available for allocation or assignment.
+    Available,
+
+    /// Any other status, holding the status text as it was found in the listing.
+    Other(String),
+}
+
+impl Status {
+    /// Returns the status as it is written in RSEF listings.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Status::Allocated => "allocated",
+            Status::Assigned => "assigned",
+            Status::Reserved => "reserved",
+            Status::Available => "available",
+            Status::Other(status) => status,
+        }
+    }
+}
+
+/// Converts a string to a Status. Unrecognized statuses are preserved as Status::Other.
+impl From<&str> for Status {
+    fn from(value: &str) -> Self {
+        let string = value.to_lowercase();
+
+        if string.eq("allocated") {
+            Status::Allocated
+        } else if string.eq("assigned") {
+            Status::Assigned
+        } else if string.eq("reserved") {
+            Status::Reserved
+        } else if string.eq("available") {
+            Status::Available
+        } else {
+            Status::Other(value.to_string())
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 /// Represents an RSEF summary line.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Summary {
     /// The registry that this record belongs to.
     pub registry: String,
@@ -78,7 +197,10 @@ pub struct Summary {
 }
 
 /// Represents an RSEF version line.
-#[derive(Debug, Clone)]
+///
+/// Since the version is a floating point number, a Version can be compared but not hashed.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Version {
     /// The version of the RIR Statistics Exchange Format.
     pub version: f64,
@@ -103,7 +225,8 @@ pub struct Version {
 }
 
 /// Represents an record about either an ASN, IPv4 prefix or IPv6 prefix.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Record {
     /// The registry that this record belongs to.
     pub registry: String,
@@ -124,77 +247,984 @@ pub struct Record {
     pub date: String,
 
     /// Type of allocation from the set.
-    pub status: String,
+    pub status: Status,
 
     /// The ID handle of this object. Often a reference to an organisation (which is also related to an AS)
     pub id: String,
+
+    /// The value of the ninth field, if the record has one. No published version of the RSEF
+    /// format defines a field after the opaque id, so its meaning is up to the registry that
+    /// appends it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub flags: Option<String>,
+
+    /// Any fields found after the flags. Empty for records that end at the id or the flags, which
+    /// is the case for nearly all listings.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extra: Vec<String>,
+}
+
+#[cfg(feature = "chrono")]
+impl Version {
+    /// Returns the start date of the time period, or None if it is not a valid yyyymmdd date.
+    ///
+    /// Requires the `chrono` feature, which is also enabled by the `download` feature.
+    pub fn start_date_parsed(&self) -> Option<chrono::NaiveDate> {
+        parse_date(&self.start_date)
+    }
+
+    /// Returns the end date of the time period, or None if it is not a valid yyyymmdd date.
+    ///
+    /// Requires the `chrono` feature, which is also enabled by the `download` feature.
+    pub fn end_date_parsed(&self) -> Option<chrono::NaiveDate> {
+        parse_date(&self.end_date)
+    }
+
+    /// Returns the UTC offset of the registry that produced the listing, or None if it is not a
+    /// valid offset in the +hhmm or -hhmm format.
+    ///
+    /// Requires the `chrono` feature, which is also enabled by the `download` feature.
+    pub fn utc_offset_parsed(&self) -> Option<chrono::FixedOffset> {
+        let offset = self.utc_offset.as_bytes();
+        if offset.len() != 5 || !offset[1..].iter().all(|x| x.is_ascii_digit()) {
+            return None;
+        }
+
+        let hours = self.utc_offset[1..3].parse::<i32>().ok()?;
+        let minutes = self.utc_offset[3..5].parse::<i32>().ok()?;
+        if minutes >= 60 {
+            return None;
+        }
+
+        let seconds = hours * 3600 + minutes * 60;
+        match offset[0] {
+            b'+' => chrono::FixedOffset::east_opt(seconds),
+            b'-' => chrono::FixedOffset::west_opt(seconds),
+            _ => None,
+        }
+    }
+}
+
+///
+/// Returns an identifier of the listing that the given version line belongs to, in the format
+/// `<registry>-<serial>-<start date>`, e.g. to use as a cache key. Listings with the same
+/// identifier are the same file published by a registry.
+///
+/// # Examples
+///
+/// ```
+/// use rsef_rs::Line;
+///
+/// let lines = rsef_rs::parse_str("2|ripencc|1549062000|2|19830705|20190201|+0100\n").unwrap();
+///
+/// if let Line::Version(version) = &lines[0] {
+///     assert_eq!(rsef_rs::listing_id(version), "ripencc-1549062000-19830705");
+/// }
+/// ```
+///
+pub fn listing_id(version: &Version) -> String {
+    format!(
+        "{}-{}-{}",
+        version.registry, version.serial, version.start_date
+    )
+}
+
+impl Record {
+    /// Returns the value of this record widened to a u64, so that address and AS number counts
+    /// can be added up without overflowing.
+    pub fn value_u64(&self) -> u64 {
+        u64::from(self.value)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Record {
+    /// Returns the date on which this allocation was made, or None if it is not a valid date.
+    ///
+    /// Requires the `chrono` feature, which is also enabled by the `download` feature.
+    pub fn date_parsed(&self) -> Option<chrono::NaiveDate> {
+        parse_date(&self.date)
+    }
+}
+
+/// Parses a date in the yyyymmdd format used by RSEF listings.
+#[cfg(feature = "chrono")]
+fn parse_date(date: &str) -> Option<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(date, "%Y%m%d").ok()
+}
+
+/// Options that control how strictly the lines of an RSEF listing are parsed.
+///
+/// The default options are strict, which is how `read_all`, `parse_str` and `LineReader::new`
+/// parse listings. Set `strict` to false to accept unknown resource types.
+///
+/// # Example
+/// ```
+/// use rsef_rs::{Line, ParseOptions, Type};
+///
+/// let listing = "ripencc|NL|ipv8|193.0.0.0|256|19930901|assigned|abc\n";
+///
+/// let lenient = ParseOptions { strict: false };
+/// let lines = rsef_rs::read_all_with_options(listing.as_bytes(), lenient).unwrap();
+/// match &lines[0] {
+///     Line::Record(x) => assert_eq!(x.res_type, Type::Unknown),
+///     _ => panic!("Expected a record line"),
+/// }
+///
+/// assert!(rsef_rs::read_all_with_options(listing.as_bytes(), ParseOptions::default()).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Whether a resource type other than `asn`, `ipv4` or `ipv6` is an error. If false, summaries
+    /// and records of an unknown resource type are accepted with `Type::Unknown` as their type.
+    pub strict: bool,
+}
+
+impl Default for ParseOptions {
+    /// Parses strictly, like `read_all` does.
+    fn default() -> Self {
+        STRICT
+    }
 }
 
 ///
 /// Reads all the RSEF entries found in a stream and returns a Vec of RSEF entries.
 ///
-pub fn read_all(read: impl Read) -> Result<impl Iterator<Item = Line>, Box<dyn Error>> {
-    let mut stream = BufReader::new(read);
-    let mut lines: Vec<Line> = Vec::new();
+/// Returns an error as soon as the stream cannot be read or a malformed line is encountered. Lines
+/// of an unknown resource type are rejected as well, like `read_all_with_options` does in strict
+/// mode.
+///
+/// Every line is classified by its own fields, so listings that lack a version line, such as
+/// slices of a listing, are parsed as well. Use `read_all_with_header` to require a version line.
+///
+pub fn read_all(read: impl Read) -> Result<impl Iterator<Item = Line>, RsefError> {
+    let lines = LineReader::new(read).collect::<Result<Vec<Line>, RsefError>>()?;
+    Ok(lines.into_iter())
+}
+
+///
+/// Reads all the RSEF entries found in a stream, parsing them according to the given options.
+///
+/// Returns an error as soon as the stream cannot be read or a malformed line is encountered. The
+/// error describes the number of the offending line and the field that could not be parsed.
+///
+pub fn read_all_with_options(
+    read: impl Read,
+    options: ParseOptions,
+) -> Result<Vec<Line>, RsefError> {
+    LineReader::with_options(read, options).collect()
+}
+
+///
+/// Reads all the RSEF entries found in a stream and returns the version line, which every listing
+/// starts with, separately from the lines that follow it.
+///
+/// Returns `RsefError::MissingVersion` if the first line of the listing, not counting comments,
+/// is not a version line, whereas `read_all` accepts such listings. Other errors are returned like
+/// `read_all` does.
+///
+/// # Examples
+///
+/// ```
+/// let listing = "2|ripencc|1|1|19830705|20190201|+0100\n\
+///                ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n";
+///
+/// let (version, lines) = rsef_rs::read_all_with_header(listing.as_bytes()).unwrap();
+///
+/// assert_eq!(version.serial, "1");
+/// assert_eq!(lines.len(), 1);
+/// ```
+///
+pub fn read_all_with_header<R: Read>(read: R) -> Result<(Version, Vec<Line>), RsefError> {
+    let mut reader = LineReader::new(read);
+
+    let version = match reader.next().transpose()? {
+        Some(Line::Version(version)) => version,
+        _ => return Err(RsefError::MissingVersion),
+    };
+
+    Ok((version, reader.collect::<Result<Vec<Line>, RsefError>>()?))
+}
+
+///
+/// Reads all the RSEF entries found in a stream like `read_all`, along with the number of the line
+/// every entry was found on, e.g. to point at the line in the original file when reporting on it.
+/// Line numbers start at 1 and count every line of the stream, including comments and blank lines.
+///
+/// # Examples
+///
+/// ```
+/// let listing = "2|ripencc|1|1|19830705|20190201|+0100\n\
+///                ## A comment\n\
+///                \n\
+///                ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n";
+///
+/// let lines = rsef_rs::read_all_indexed(listing.as_bytes()).unwrap();
+///
+/// assert_eq!(lines[0].0, 1);
+/// assert_eq!(lines[1].0, 4);
+/// ```
+///
+pub fn read_all_indexed<R: Read>(read: R) -> Result<Vec<(usize, Line)>, RsefError> {
+    let mut reader = LineReader::new(read);
+    let mut lines = Vec::new();
+
+    while let Some(line) = reader.next() {
+        lines.push((reader.number, line?));
+    }
+
+    Ok(lines)
+}
+
+///
+/// Reads all the RSEF entries found in a stream like `read_all`, but keeps going past malformed
+/// lines. Returns the lines that were parsed along with an error for every line that was not.
+///
+/// Reading stops at the first error of the underlying stream, which is returned as the last error.
+///
+pub fn read_all_collect_errors<R: Read>(read: R) -> (Vec<Line>, Vec<LineError>) {
+    let mut reader = LineReader::new(read);
+    let mut lines = Vec::new();
+    let mut errors = Vec::new();
+    let mut io_error = None;
+
+    for line in reader.by_ref() {
+        match line {
+            Ok(line) => lines.push(line),
+            Err(RsefError::Parse(err)) => errors.push(LineError {
+                line: err.line,
+                message: err.to_string(),
+            }),
+            Err(err) => {
+                io_error = Some(err);
+                break;
+            }
+        }
+    }
+
+    if let Some(err) = io_error {
+        errors.push(LineError {
+            line: reader.number + 1,
+            message: err.to_string(),
+        });
+    }
+
+    (lines, errors)
+}
+
+///
+/// Reads the records found in a stream that match the given predicate. Every record is parsed
+/// and checked as it is read, so records that do not match are never held in memory. Version and
+/// summary lines are skipped.
+///
+/// Returns an error as soon as the stream cannot be read or a malformed line is encountered.
+///
+/// # Examples
+///
+/// ```
+/// let listing = "2|ripencc|1|2|19830705|20190201|+0100\n\
+///                ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+///                ripencc|NL|ipv4|2.56.0.0|1024|20230112|allocated|def\n";
+///
+/// let records = rsef_rs::read_filtered(listing.as_bytes(), |record| {
+///     record.date.starts_with("2023")
+/// })
+/// .unwrap();
+///
+/// assert_eq!(records.len(), 1);
+/// assert_eq!(records[0].start, "2.56.0.0");
+/// ```
+///
+pub fn read_filtered<R: Read>(
+    read: R,
+    predicate: impl Fn(&Record) -> bool,
+) -> Result<Vec<Record>, RsefError> {
+    let mut records = Vec::new();
+
+    for line in LineReader::new(read) {
+        if let Line::Record(record) = line? {
+            if predicate(&record) {
+                records.push(record);
+            }
+        }
+    }
+
+    Ok(records)
+}
+
+///
+/// Parses all the RSEF entries found in a string, e.g. a listing that is already in memory.
+///
+/// Returns an error as soon as a malformed line is encountered, like `read_all`.
+///
+/// # Examples
+///
+/// ```
+/// use rsef_rs::Line;
+///
+/// let lines = rsef_rs::parse_str(
+///     "2|ripencc|1|1|19830705|20190201|+0100\n\
+///      ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n",
+/// )
+/// .unwrap();
+///
+/// assert_eq!(lines.len(), 2);
+/// assert!(matches!(lines[1], Line::Record(_)));
+/// ```
+///
+pub fn parse_str(s: &str) -> Result<Vec<Line>, RsefError> {
+    LineReader::new(s.as_bytes()).collect()
+}
+
+///
+/// Reads all the RSEF entries found in an asynchronous stream, parsing every line as soon as it
+/// has been received. Requires the `tokio` feature, which is enabled by the `download` feature.
+///
+/// Returns an error as soon as the stream cannot be read or a malformed line is encountered.
+///
+#[cfg(feature = "tokio")]
+pub async fn read_all_async<R: AsyncRead + Unpin>(read: R) -> Result<Vec<Line>, RsefError> {
+    let mut stream = tokio::io::BufReader::new(read);
+    let mut buffer = String::new();
+    let mut lines = Vec::new();
+    let mut number = 0;
 
     loop {
-        let mut line = String::new();
-        let len = stream.read_line(&mut line)?;
-
-        if len == 0 {
-            break;
-        }
-
-        // Remove the trailing whitespaces and newline characters
-        line.pop();
-
-        // Skip the comments.
-        if line.starts_with('#') {
-            continue;
-        }
-
-        // Divide the line into fields.
-        let fields = line.split('|').collect::<Vec<_>>();
-
-        // Check if line is a version.
-        if fields[0].chars().all(|x| x.is_digit(10) || x.eq(&'.')) {
-            lines.push(Line::Version(Version {
-                version: fields[0].parse::<f64>().unwrap(),
-                registry: fields[1].to_string(),
-                serial: fields[2].to_string(),
-                records: fields[3].parse::<u32>().unwrap(),
-                start_date: fields[4].to_string(),
-                end_date: fields[5].to_string(),
-                utc_offset: fields[6].to_string(),
-            }));
-            continue;
-        }
-
-        // Check if line is a summary.
-        if fields[5].to_string().eq("summary") {
-            lines.push(Line::Summary(Summary {
-                registry: fields[0].to_string(),
-                res_type: Type::try_from(fields[2])?,
-                count: fields[4].parse::<u32>().unwrap(),
-            }));
-            continue;
-        }
-
-        lines.push(Line::Record(Record {
-            registry: fields[0].to_string(),
-            organization: fields[1].to_string(),
-            res_type: Type::try_from(fields[2])?,
-            start: fields[3].to_string(),
-            value: fields[4].parse::<u32>().unwrap(),
-            date: fields[5].to_string(),
-            status: fields[6].to_string(),
-            id: if fields.len() > 7 {
-                fields[7].to_string()
-            } else {
-                "".to_string()
-            },
+        buffer.clear();
+        if stream.read_line(&mut buffer).await? == 0 {
+            return Ok(lines);
+        }
+
+        number += 1;
+        if let Some(line) = parse_raw_line(number, &buffer, STRICT, &mut None) {
+            lines.push(line?);
+        }
+    }
+}
+
+///
+/// Reads all the RSEF entries found in a file. Compressed files are decompressed transparently,
+/// see `decode` for the supported compression formats.
+///
+pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Vec<Line>, RsefError> {
+    let file = File::open(path)?;
+    LineReader::new(decode(file)?).collect()
+}
+
+///
+/// Reads all the RSEF entries found in a stream like `read_all`, but also returns the comment
+/// lines that are otherwise skipped. The header comments often contain useful metadata such as the
+/// time at which the listing was generated. Comments are returned as found, including the `#`.
+///
+pub fn read_all_with_comments(read: impl Read) -> Result<(Vec<Line>, Vec<String>), RsefError> {
+    let mut reader = LineReader::new(read);
+    reader.comments = Some(Vec::new());
+
+    let lines = reader.by_ref().collect::<Result<Vec<Line>, RsefError>>()?;
+    Ok((lines, reader.comments.unwrap_or_default()))
+}
+
+///
+/// Lazily reads the RSEF entries found in a stream, parsing a single line at a time.
+///
+/// Every call to `next` yields either the next Version, Summary or Record line or the error that
+/// was encountered while reading or parsing it. Reading may continue after a parse error.
+///
+/// # Examples
+///
+/// ```
+/// use rsef_rs::{Line, LineReader, Type};
+///
+/// let listing = "2|ripencc|1|2|19830705|20190201|+0100\n\
+///                ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+///                ripencc|NL|asn|3333|1|19930901|assigned|abc\n";
+///
+/// let mut ipv4 = 0;
+/// for line in LineReader::new(listing.as_bytes()) {
+///     if let Line::Record(record) = line.unwrap() {
+///         if let Type::IPv4 = record.res_type {
+///             ipv4 += 1;
+///         }
+///     }
+/// }
+///
+/// assert_eq!(ipv4, 1);
+/// ```
+///
+pub struct LineReader<B> {
+    stream: B,
+    number: usize,
+    buffer: String,
+    comments: Option<Vec<String>>,
+    options: ParseOptions,
+}
+
+impl<R: Read> LineReader<BufReader<R>> {
+    /// Creates a new LineReader that reads the RSEF entries from the given stream. Lines of an
+    /// unknown resource type are rejected.
+    pub fn new(read: R) -> Self {
+        Self::with_options(read, STRICT)
+    }
+
+    /// Creates a new LineReader that reads the RSEF entries from the given stream, parsing them
+    /// according to the given options.
+    pub fn with_options(read: R, options: ParseOptions) -> Self {
+        LineReader {
+            options,
+            ..LineReader::from_buf_read(BufReader::new(read))
+        }
+    }
+}
+
+impl<B: BufRead> LineReader<B> {
+    /// Creates a new LineReader that reads the RSEF entries from a buffered stream. Lines of an
+    /// unknown resource type are rejected.
+    ///
+    /// Lines are read from the stream one at a time, so passing `&mut` a reader allows parsing to
+    /// stop at any line and to continue from the same reader later, e.g. to parse a stream that
+    /// carries multiple concatenated listings. Line numbers start at 1 for every LineReader.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rsef_rs::{Line, LineReader};
+    /// use std::io::BufReader;
+    ///
+    /// let listings = "2|ripencc|1|1|19830705|20190201|+0100\n\
+    ///                 ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+    ///                 2|arin|1|1|19830705|20190201|-0500\n\
+    ///                 arin|US|ipv4|8.8.8.0|256|19921201|allocated|def\n";
+    /// let mut stream = BufReader::new(listings.as_bytes());
+    ///
+    /// let first = LineReader::from_buf_read(&mut stream).take(2).collect::<Result<Vec<_>, _>>();
+    /// let second = LineReader::from_buf_read(&mut stream).collect::<Result<Vec<_>, _>>();
+    ///
+    /// assert!(matches!(&first.unwrap()[0], Line::Version(version) if version.registry == "ripencc"));
+    /// assert!(matches!(&second.unwrap()[0], Line::Version(version) if version.registry == "arin"));
+    /// ```
+    pub fn from_buf_read(stream: B) -> Self {
+        LineReader {
+            stream,
+            number: 0,
+            buffer: String::new(),
+            comments: None,
+            options: STRICT,
+        }
+    }
+}
+
+impl<B: BufRead> Iterator for LineReader<B> {
+    type Item = Result<Line, RsefError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buffer.clear();
+
+            match self.stream.read_line(&mut self.buffer) {
+                Ok(0) => return None,
+                Ok(_) => self.number += 1,
+                Err(err) => return Some(Err(RsefError::from(err))),
+            }
+
+            let line = parse_raw_line(self.number, &self.buffer, self.options, &mut self.comments);
+
+            if let Some(line) = line {
+                return Some(line.map_err(RsefError::from));
+            }
+        }
+    }
+}
+
+/// The options that the functions which do not take any options parse listings with.
+const STRICT: ParseOptions = ParseOptions { strict: true };
+
+/// Parses a line as it was read from a listing, including its line ending. Returns None for the
+/// comments and blank lines, which are skipped, adding the comments to `comments` if it is Some.
+fn parse_raw_line(
+    number: usize,
+    raw: &str,
+    options: ParseOptions,
+    comments: &mut Option<Vec<String>>,
+) -> Option<Result<Line, ParseError>> {
+    // Remove the trailing newline characters, including the carriage return of CRLF endings.
+    let line = raw.trim_end_matches(['\r', '\n']);
+
+    if line.starts_with('#') {
+        if let Some(comments) = comments.as_mut() {
+            comments.push(line.to_string());
+        }
+        return None;
+    }
+
+    if line.trim().is_empty() {
+        return None;
+    }
+
+    Some(parse_line(number, line, options))
+}
+
+/// Parses a single, non-comment and non-blank line of an RSEF listing.
+fn parse_line(number: usize, line: &str, options: ParseOptions) -> Result<Line, ParseError> {
+    // Divide the line into fields, ignoring any whitespace that pads them.
+    let fields = Fields {
+        number,
+        line,
+        values: line.split('|').map(str::trim).collect(),
+        strict: options.strict,
+    };
+
+    // Check if line is a version.
+    if fields.is_version() {
+        return Ok(Line::Version(Version {
+            version: fields.parse(0, "version")?,
+            registry: fields.get(1, "registry")?.to_string(),
+            serial: fields.get(2, "serial")?.to_string(),
+            records: fields.parse(3, "records")?,
+            start_date: fields.get(4, "startdate")?.to_string(),
+            end_date: fields.get(5, "enddate")?.to_string(),
+            utc_offset: fields.get(6, "UTCoffset")?.to_string(),
         }));
     }
 
-    Ok(lines.into_iter())
+    // Check if line is a summary.
+    if fields.get(5, "summary")? == "summary" {
+        return Ok(Line::Summary(Summary {
+            registry: fields.get(0, "registry")?.to_string(),
+            res_type: fields.res_type(2)?,
+            count: fields.parse(4, "count")?,
+        }));
+    }
+
+    Ok(Line::Record(Record {
+        registry: fields.get(0, "registry")?.to_string(),
+        organization: fields.get(1, "cc")?.to_string(),
+        res_type: fields.res_type(2)?,
+        start: fields.get(3, "start")?.to_string(),
+        value: fields.parse(4, "value")?,
+        date: fields.get(5, "date")?.to_string(),
+        status: Status::from(fields.get(6, "status")?),
+        id: fields.values.get(7).copied().unwrap_or("").to_string(),
+        flags: fields.values.get(8).map(|x| x.to_string()),
+        extra: fields
+            .values
+            .iter()
+            .skip(9)
+            .map(|x| x.to_string())
+            .collect(),
+    }))
+}
+
+/// The `|`-delimited fields of a single line, along with the context needed to report errors.
+struct Fields<'a> {
+    number: usize,
+    line: &'a str,
+    values: Vec<&'a str>,
+    strict: bool,
+}
+
+impl<'a> Fields<'a> {
+    /// Creates a ParseError for the given field of this line.
+    fn error(&self, field: &'static str, message: String) -> ParseError {
+        ParseError {
+            line: self.number,
+            text: self.line.to_string(),
+            field,
+            message,
+        }
+    }
+
+    /// Returns whether these are the fields of a version line: exactly seven fields of which the
+    /// first is a version number. A classic record also has seven fields, so the third field must
+    /// not be a resource type either.
+    fn is_version(&self) -> bool {
+        let version = self.values[0];
+
+        self.values.len() == 7
+            && !version.is_empty()
+            && version.chars().all(|x| x.is_ascii_digit() || x.eq(&'.'))
+            && Type::try_from(self.values[2]).is_err()
+    }
+
+    /// Returns the field at the given index or an error if the line has too few fields.
+    fn get(&self, index: usize, field: &'static str) -> Result<&'a str, ParseError> {
+        self.values.get(index).copied().ok_or_else(|| {
+            self.error(
+                field,
+                format!(
+                    "expected at least {} fields but found {}",
+                    index + 1,
+                    self.values.len()
+                ),
+            )
+        })
+    }
+
+    /// Parses the field at the given index into a value of type T.
+    fn parse<T: FromStr>(&self, index: usize, field: &'static str) -> Result<T, ParseError>
+    where
+        T::Err: fmt::Display,
+    {
+        let value = self.get(index, field)?;
+        value
+            .parse::<T>()
+            .map_err(|err| self.error(field, format!("'{}' is invalid: {}", value, err)))
+    }
+
+    /// Parses the field at the given index into a resource Type. Unknown resource types are only
+    /// an error when parsing strictly.
+    fn res_type(&self, index: usize) -> Result<Type, ParseError> {
+        match Type::try_from(self.get(index, "type")?) {
+            Ok(res_type) => Ok(res_type),
+            Err(_) if !self.strict => Ok(Type::Unknown),
+            Err(err) => Err(self.error("type", err.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        read_all, read_all_collect_errors, read_all_with_comments, read_all_with_header,
+        read_all_with_options, Line, ParseOptions, RsefError, Status, Type,
+    };
+
+    use std::collections::HashSet;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_malformed_line() {
+        let listing = "2|ripencc|1|1|19830705|20190201|+0100\n\
+                       ripencc|NL|ipv4|193.0.0.0|many|19930901|assigned|abc\n";
+
+        match read_all(listing.as_bytes()) {
+            Err(RsefError::Parse(err)) => {
+                assert_eq!(err.line, 2);
+                assert_eq!(err.field, "value");
+                assert!(err.text.starts_with("ripencc|NL|ipv4"));
+            }
+            _ => panic!("Expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn test_read_all_collect_errors() {
+        let listing = "2|ripencc|1|3|19830705|20190201|+0100\n\
+                       ripencc|NL|ipv4|193.0.0.0|many|19930901|assigned|abc\n\
+                       ripencc|NL|ipv4|193.0.4.0|256|19930901|assigned|abc\n\
+                       # comment\n\
+                       ripencc|NL|ipv4\n\
+                       ripencc|NL|asn|3333|1|19930901|assigned|abc\n";
+
+        let (lines, errors) = read_all_collect_errors(listing.as_bytes());
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            errors.iter().map(|err| err.line).collect::<Vec<_>>(),
+            vec![2, 5]
+        );
+        assert!(errors[0].message.contains("'value'"));
+
+        let (lines, errors) = read_all_collect_errors(&[0xff, 0xfe, b'\n'][..]);
+        assert!(lines.is_empty());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 1);
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        let listing = "2|ripencc|1|1|19830705|20190201|+0100\r\n\
+                       ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\r\n";
+
+        let lines = read_all(listing.as_bytes()).unwrap().collect::<Vec<_>>();
+
+        match &lines[0] {
+            Line::Version(x) => assert_eq!(x.utc_offset, "+0100"),
+            _ => panic!("Expected a version line"),
+        }
+
+        match &lines[1] {
+            Line::Record(x) => {
+                assert_eq!(x.status, Status::Assigned);
+                assert_eq!(x.id, "abc");
+            }
+            _ => panic!("Expected a record line"),
+        }
+    }
+
+    #[test]
+    fn test_read_all_with_comments() {
+        let listing = "# Generated on 20190201\n\
+                       2|ripencc|1|1|19830705|20190201|+0100\n\
+                       #records\n\
+                       ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n";
+
+        let (lines, comments) = read_all_with_comments(listing.as_bytes()).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(comments, vec!["# Generated on 20190201", "#records"]);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_read_all_async() {
+        let listing = "# Generated on 20190201\r\n\
+                       2|ripencc|1|1|19830705|20190201|+0100\r\n\
+                       ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\r\n";
+
+        let lines = crate::read_all_async(listing.as_bytes()).await.unwrap();
+        assert_eq!(
+            lines,
+            read_all(listing.as_bytes()).unwrap().collect::<Vec<_>>()
+        );
+        assert_eq!(lines.len(), 2);
+
+        let err = crate::read_all_async("ripencc|NL|ipv4\n".as_bytes()).await;
+        assert!(matches!(err, Err(RsefError::Parse(_))));
+    }
+
+    #[test]
+    fn test_blank_lines() {
+        let listing = "2|ripencc|1|2|19830705|20190201|+0100\n\
+                       ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+                       \n\
+                       ripencc|DE|ipv4|193.0.4.0|256|19930901|assigned|def\n";
+
+        let lines = read_all(listing.as_bytes()).unwrap().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn test_classic_record() {
+        let listing = "apnic|JP|ipv4|1.0.16.0|4096|20110412|allocated\n";
+
+        match read_all(listing.as_bytes()).unwrap().next() {
+            Some(Line::Record(x)) => {
+                assert_eq!(x.status, Status::Allocated);
+                assert_eq!(x.id, "");
+            }
+            _ => panic!("Expected a record line"),
+        }
+    }
+
+    #[test]
+    fn test_numeric_record_fields() {
+        let listing = "2|ripencc|1|2|19830705|20190201|+0100\n\
+                       2|NL|asn|3333|1|19930901|assigned|abc\n\
+                       2|NL|asn|3320|1|19930901|assigned\n";
+
+        let lines = read_all(listing.as_bytes()).unwrap().collect::<Vec<_>>();
+        assert!(matches!(lines[0], Line::Version(_)));
+
+        for line in &lines[1..] {
+            match line {
+                Line::Record(x) => assert_eq!(x.registry, "2"),
+                _ => panic!("Expected a record line"),
+            }
+        }
+
+        match read_all("|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n".as_bytes()) {
+            Ok(mut lines) => assert!(matches!(lines.next(), Some(Line::Record(_)))),
+            Err(_) => panic!("Expected a record line"),
+        }
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let listing = "2|ripencc|1|1|19830705|20190201|+0100\n\
+                       ripencc|*|ipv8|*|1|summary\n\
+                       ripencc|NL|ipv8|193.0.0.0|256|19930901|assigned|abc\n";
+
+        let lenient = ParseOptions { strict: false };
+        let lines = read_all_with_options(listing.as_bytes(), lenient).unwrap();
+        assert!(matches!(&lines[1], Line::Summary(x) if x.res_type == Type::Unknown));
+        assert!(matches!(&lines[2], Line::Record(x) if x.res_type == Type::Unknown));
+
+        match read_all_with_options(listing.as_bytes(), ParseOptions::default()) {
+            Err(RsefError::Parse(err)) => {
+                assert_eq!(err.line, 2);
+                assert_eq!(err.field, "type");
+            }
+            _ => panic!("Expected a parse error"),
+        }
+
+        assert!(read_all(listing.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_extra_fields() {
+        let listing = "ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc|e-stats|x\n\
+                       ripencc|NL|ipv4|193.0.4.0|256|19930901|assigned|abc\n";
+
+        let lines = read_all(listing.as_bytes()).unwrap().collect::<Vec<_>>();
+
+        match &lines[0] {
+            Line::Record(x) => {
+                assert_eq!(x.id, "abc");
+                assert_eq!(x.flags.as_deref(), Some("e-stats"));
+                assert_eq!(x.extra, vec!["x"]);
+            }
+            _ => panic!("Expected a record line"),
+        }
+
+        match &lines[1] {
+            Line::Record(x) => {
+                assert_eq!(x.flags, None);
+                assert!(x.extra.is_empty());
+            }
+            _ => panic!("Expected a record line"),
+        }
+    }
+
+    #[test]
+    fn test_padded_fields() {
+        let listing = "2 | ripencc | 1 | 1 | 19830705 | 20190201 | +0100\n\
+                       ripencc | DE | ipv4 | 193.0.0.0 | 256 | 19930901 | assigned | abc \n";
+
+        let lines = read_all(listing.as_bytes()).unwrap().collect::<Vec<_>>();
+
+        match &lines[0] {
+            Line::Version(x) => assert_eq!(x.records, 1),
+            _ => panic!("Expected a version line"),
+        }
+
+        match &lines[1] {
+            Line::Record(x) => {
+                assert_eq!(x.organization, "DE");
+                assert_eq!(x.res_type, Type::IPv4);
+                assert_eq!(x.value, 256);
+                assert_eq!(x.status, Status::Assigned);
+                assert_eq!(x.id, "abc");
+            }
+            _ => panic!("Expected a record line"),
+        }
+    }
+
+    #[test]
+    fn test_truncated_line() {
+        let listing = "ripencc|NL|ipv4\n";
+
+        match read_all(listing.as_bytes()) {
+            Err(RsefError::Parse(err)) => assert_eq!(err.line, 1),
+            _ => panic!("Expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn test_missing_header() {
+        let listing = "ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n";
+
+        assert!(matches!(
+            read_all_with_header(listing.as_bytes()),
+            Err(RsefError::MissingVersion)
+        ));
+        assert!(matches!(
+            read_all_with_header("".as_bytes()),
+            Err(RsefError::MissingVersion)
+        ));
+    }
+
+    #[test]
+    fn test_status() {
+        assert_eq!(Status::from("allocated"), Status::Allocated);
+        assert_eq!(Status::from("ASSIGNED"), Status::Assigned);
+        assert_eq!(Status::from("reserved"), Status::Reserved);
+        assert_eq!(Status::from("available"), Status::Available);
+        assert_eq!(Status::from("legacy"), Status::Other("legacy".to_string()));
+        assert_eq!(Status::Other("legacy".to_string()).to_string(), "legacy");
+    }
+
+    #[test]
+    fn test_records_eq_and_hash() {
+        let listing = "ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+                       ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+                       ripencc|NL|asn|3333|1|19930901|assigned|abc\n";
+
+        let records = read_all(listing.as_bytes())
+            .unwrap()
+            .filter_map(|line| match line {
+                Line::Record(x) => Some(x),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(records[0], records[1]);
+        assert_ne!(records[0], records[2]);
+        assert_eq!(records.into_iter().collect::<HashSet<_>>().len(), 2);
+    }
+
+    #[test]
+    fn test_type_display() {
+        let parsed = Type::try_from(&Type::IPv4.to_string()[..]).unwrap();
+        assert_eq!(parsed, Type::IPv4);
+
+        assert_eq!(Type::ASN.to_string(), "asn");
+        assert_eq!(Type::IPv6.to_string(), "ipv6");
+        assert_eq!(Type::Unknown.to_string(), "unknown");
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn test_dates_parsed() {
+        use chrono::NaiveDate;
+
+        let listing = "2|ripencc|1|2|19830705|20190231|+0100\n\
+                       ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n";
+        let lines = read_all(listing.as_bytes()).unwrap().collect::<Vec<_>>();
+
+        match &lines[0] {
+            Line::Version(x) => {
+                assert_eq!(x.start_date_parsed(), NaiveDate::from_ymd_opt(1983, 7, 5));
+                assert_eq!(x.end_date_parsed(), None);
+            }
+            _ => panic!("Expected a version line"),
+        }
+
+        match &lines[1] {
+            Line::Record(x) => assert_eq!(x.date_parsed(), NaiveDate::from_ymd_opt(1993, 9, 1)),
+            _ => panic!("Expected a record line"),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn test_utc_offset_parsed() {
+        use chrono::FixedOffset;
+
+        let mut version = crate::Version {
+            version: 2.0,
+            registry: "ripencc".to_string(),
+            serial: "1".to_string(),
+            records: 0,
+            start_date: "19830705".to_string(),
+            end_date: "20190201".to_string(),
+            utc_offset: "+0100".to_string(),
+        };
+        assert_eq!(version.utc_offset_parsed(), FixedOffset::east_opt(3600));
+
+        version.utc_offset = "-0530".to_string();
+        assert_eq!(version.utc_offset_parsed(), FixedOffset::west_opt(19800));
+
+        version.utc_offset = "+0000".to_string();
+        assert_eq!(version.utc_offset_parsed(), FixedOffset::east_opt(0));
+
+        for malformed in &["", "0100", "+1", "+01:00", "+0160", "+2400", "*0100"] {
+            version.utc_offset = malformed.to_string();
+            assert_eq!(version.utc_offset_parsed(), None);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde_round_trip() {
+        let listing = "ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+                       ripencc|ZZ|ipv6|2001:67c:2e8::|48|19930901|reserved\n";
+
+        for line in read_all(listing.as_bytes()).unwrap() {
+            let json = serde_json::to_string(&line).unwrap();
+            let parsed: Line = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed, line);
+        }
+
+        let json = serde_json::to_string(&Type::Unknown).unwrap();
+        let parsed: Type = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, Type::Unknown);
+    }
 }
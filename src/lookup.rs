@@ -0,0 +1,236 @@
+//!
+//! Functionality to find the records that own a given Internet resource.
+//!
+
+use crate::{records, Line, Record};
+
+#[cfg(feature = "ipnet")]
+use ipnet::IpNet;
+
+use std::net::IpAddr;
+
+///
+/// Finds the ASN record whose block of AS numbers contains the given AS number. ASN records store
+/// the first AS number of the block in `start` and the number of AS numbers in `value`.
+///
+pub fn find_asn(lines: &[Line], asn: u32) -> Option<&Record> {
+    records(lines).find(|record| match record.asn_range() {
+        Some((start, end)) => start <= asn && asn <= end,
+        None => false,
+    })
+}
+
+///
+/// Returns the records in the index whose range intersects the given block of addresses, ordered
+/// by their first address. Together these records cover the whole block if it is fully
+/// registered, e.g. to check whether a route seen in BGP corresponds to a registered allocation.
+///
+/// Requires the `ipnet` feature.
+///
+#[cfg(feature = "ipnet")]
+pub fn covering_records<'a>(index: &IpIndex<'a>, net: IpNet) -> Vec<&'a Record> {
+    match net {
+        IpNet::V4(net) => index
+            .ipv4
+            .intersecting(u32::from(net.network()), u32::from(net.broadcast())),
+        IpNet::V6(net) => index
+            .ipv6
+            .intersecting(u128::from(net.network()), u128::from(net.broadcast())),
+    }
+}
+
+/// An index over the IP ranges of a listing that finds the record owning an address in
+/// logarithmic time.
+///
+/// # Example
+/// ```
+/// use rsef_rs::{read_all, IpIndex, Line};
+///
+/// let lines = read_all("arin|US|ipv4|8.8.8.0|256|19921201|allocated|abc\n".as_bytes())
+///     .unwrap()
+///     .collect::<Vec<Line>>();
+///
+/// let index = IpIndex::new(&lines);
+/// let record = index.lookup("8.8.8.8".parse().unwrap()).unwrap();
+/// assert_eq!(record.organization, "US");
+/// ```
+#[derive(Debug, Clone)]
+pub struct IpIndex<'a> {
+    ipv4: Ranges<'a, u32>,
+    ipv6: Ranges<'a, u128>,
+}
+
+impl<'a> IpIndex<'a> {
+    /// Builds an index over the IPv4 and IPv6 records of a listing. Records that do not describe
+    /// a valid range of addresses are left out.
+    pub fn new(lines: &'a [Line]) -> IpIndex<'a> {
+        let mut ipv4 = Vec::new();
+        let mut ipv6 = Vec::new();
+
+        for record in records(lines) {
+            if let Some((start, end)) = record.ipv4_range() {
+                ipv4.push((start, end, record));
+            } else if let Some((start, end)) = record.ipv6_range() {
+                ipv6.push((start, end, record));
+            }
+        }
+
+        IpIndex {
+            ipv4: Ranges::new(ipv4),
+            ipv6: Ranges::new(ipv6),
+        }
+    }
+
+    /// Returns the record whose range contains the given address. If several ranges contain the
+    /// address, the one that starts closest to it is returned.
+    pub fn lookup(&self, addr: IpAddr) -> Option<&'a Record> {
+        match addr {
+            IpAddr::V4(addr) => self.ipv4.lookup(u32::from(addr)),
+            IpAddr::V6(addr) => self.ipv6.lookup(u128::from(addr)),
+        }
+    }
+
+    /// Returns the number of ranges in the index.
+    pub fn len(&self) -> usize {
+        self.ipv4.ranges.len() + self.ipv6.ranges.len()
+    }
+
+    /// Returns true if the index does not contain any ranges.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Address ranges sorted by their first address.
+#[derive(Debug, Clone)]
+struct Ranges<'a, T> {
+    ranges: Vec<(T, T, &'a Record)>,
+
+    /// The highest last address of all ranges up to and including the one at the same position,
+    /// which allows the search to stop early when ranges overlap.
+    max_end: Vec<T>,
+}
+
+impl<'a, T: Ord + Copy> Ranges<'a, T> {
+    fn new(mut ranges: Vec<(T, T, &'a Record)>) -> Ranges<'a, T> {
+        ranges.sort_by_key(|&(start, end, _)| (start, end));
+
+        let mut max_end: Vec<T> = Vec::with_capacity(ranges.len());
+        for &(_, end, _) in &ranges {
+            let max = max_end.last().map_or(end, |&max| max.max(end));
+            max_end.push(max);
+        }
+
+        Ranges { ranges, max_end }
+    }
+
+    fn lookup(&self, addr: T) -> Option<&'a Record> {
+        let after = self.ranges.partition_point(|&(start, _, _)| start <= addr);
+
+        (0..after)
+            .rev()
+            .take_while(|&i| self.max_end[i] >= addr)
+            .find(|&i| self.ranges[i].1 >= addr)
+            .map(|i| self.ranges[i].2)
+    }
+
+    /// Returns the records whose range shares at least one address with the range from start up
+    /// to and including end.
+    #[cfg(feature = "ipnet")]
+    fn intersecting(&self, start: T, end: T) -> Vec<&'a Record> {
+        let first = self.max_end.partition_point(|&max| max < start);
+        let after = self
+            .ranges
+            .partition_point(|&(range_start, _, _)| range_start <= end);
+
+        self.ranges[first..after.max(first)]
+            .iter()
+            .filter(|&&(_, range_end, _)| range_end >= start)
+            .map(|&(_, _, record)| record)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{find_asn, read_all, IpIndex, Line};
+
+    #[test]
+    fn test_lookup() {
+        let lines = read_all(
+            "arin|US|ipv4|8.8.8.0|256|19921201|allocated|a\n\
+             ripencc|NL|ipv4|193.0.0.0|2048|19930901|assigned|b\n\
+             ripencc|NL|ipv4|193.0.4.0|256|19930901|assigned|c\n\
+             ripencc|NL|ipv6|2001:67c:2e8::|48|20110111|assigned|d\n\
+             ripencc|DE|asn|3320|1|19930901|assigned|e\n"
+                .as_bytes(),
+        )
+        .unwrap()
+        .collect::<Vec<Line>>();
+
+        let index = IpIndex::new(&lines);
+        assert_eq!(index.len(), 4);
+
+        let lookup = |addr: &str| index.lookup(addr.parse().unwrap()).map(|r| r.id.as_str());
+        assert_eq!(lookup("8.8.8.8"), Some("a"));
+        assert_eq!(lookup("8.8.9.0"), None);
+        assert_eq!(lookup("193.0.0.1"), Some("b"));
+        assert_eq!(lookup("193.0.4.1"), Some("c"));
+        assert_eq!(lookup("193.0.7.255"), Some("b"));
+        assert_eq!(lookup("193.0.8.0"), None);
+        assert_eq!(lookup("2001:67c:2e8:22::c100:68b"), Some("d"));
+        assert_eq!(lookup("2001:67c:2e9::"), None);
+        assert_eq!(lookup("1.1.1.1"), None);
+    }
+
+    #[test]
+    fn test_find_asn() {
+        let lines = read_all(
+            "arin|US|asn|64496|4|20000101|assigned|a\n\
+             ripencc|NL|asn|3333|1|19930901|assigned|b\n\
+             ripencc|NL|ipv4|193.0.0.0|2048|19930901|assigned|c\n"
+                .as_bytes(),
+        )
+        .unwrap()
+        .collect::<Vec<Line>>();
+
+        let find = |asn| find_asn(&lines, asn).map(|r| r.id.as_str());
+        assert_eq!(find(64495), None);
+        assert_eq!(find(64496), Some("a"));
+        assert_eq!(find(64499), Some("a"));
+        assert_eq!(find(64500), None);
+        assert_eq!(find(3333), Some("b"));
+        assert_eq!(find(3334), None);
+    }
+
+    #[test]
+    #[cfg(feature = "ipnet")]
+    fn test_covering_records() {
+        use crate::covering_records;
+
+        let lines = read_all(
+            "ripencc|NL|ipv4|193.0.0.0|1024|19930901|assigned|a\n\
+             ripencc|NL|ipv4|193.0.4.0|256|19930901|assigned|b\n\
+             ripencc|NL|ipv4|193.0.2.0|256|19930901|assigned|c\n\
+             ripencc|NL|ipv4|193.0.8.0|256|19930901|assigned|d\n\
+             ripencc|NL|ipv6|2001:67c:2e8::|48|20110111|assigned|e\n"
+                .as_bytes(),
+        )
+        .unwrap()
+        .collect::<Vec<Line>>();
+
+        let index = IpIndex::new(&lines);
+        let covering = |net: &str| {
+            covering_records(&index, net.parse().unwrap())
+                .iter()
+                .map(|r| r.id.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(covering("193.0.2.0/24"), vec!["a", "c"]);
+        assert_eq!(covering("193.0.0.0/21"), vec!["a", "c", "b"]);
+        assert_eq!(covering("193.0.6.0/23"), Vec::<&str>::new());
+        assert_eq!(covering("2001:67c::/32"), vec!["e"]);
+        assert_eq!(covering("2001:67c:2e8:1::/64"), vec!["e"]);
+    }
+}
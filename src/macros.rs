@@ -0,0 +1,12 @@
+//!
+//! Internal logging macros that forward to `tracing` when the `tracing` feature is enabled and
+//! expand to nothing otherwise.
+//!
+
+/// Emits a debug level event if the `tracing` feature is enabled.
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
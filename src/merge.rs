@@ -0,0 +1,229 @@
+//!
+//! Functionality to combine the RSEF listings of several registries into a single listing, and
+//! the records within a listing into fewer ranges.
+//!
+
+use crate::{into_records, sort_records, Line, Record, Status, Type};
+
+use std::collections::HashSet;
+use std::net::Ipv4Addr;
+
+///
+/// Merges the records of several RSEF listings, e.g. the listings of all the Regional Internet
+/// Registries, into a single listing. The version and summary lines of the listings are dropped,
+/// as they only describe the listing they were found in.
+///
+/// The records are sorted by resource type and then by the AS number or address they start at, see
+/// `sort_records`.
+///
+pub fn merge(listings: Vec<Vec<Line>>) -> Vec<Line> {
+    let mut records = listings
+        .into_iter()
+        .flat_map(into_records)
+        .collect::<Vec<Record>>();
+
+    sort_records(&mut records);
+    records.into_iter().map(Line::Record).collect()
+}
+
+///
+/// Removes the records that duplicate an earlier record, e.g. after merging listings that overlap.
+/// Records are considered duplicates when their registry, resource type, start, value and status
+/// are equal. The order of the remaining lines is preserved and lines other than records are kept.
+///
+pub fn dedup(lines: &mut Vec<Line>) {
+    let mut seen = HashSet::new();
+
+    lines.retain(|line| match line {
+        Line::Record(record) => seen.insert(dedup_key(record)),
+        _ => true,
+    });
+}
+
+///
+/// Combines the ranges of the given IPv4 records into the minimal list of ranges that covers the
+/// same addresses, merging ranges that overlap or that are adjacent, such as a range ending at
+/// `193.0.3.255` and one starting at `193.0.4.0`. The ranges are returned as their first and last
+/// address, sorted by their first address. Records that do not describe a valid range of IPv4
+/// addresses are ignored.
+///
+/// Only the addresses are taken into account: the registry, country, status and organization (`id`)
+/// of the records are ignored, so adjacent ranges held by different organizations are merged as
+/// well. To coalesce the ranges of each organization separately, group the records by `id` first.
+///
+/// # Example
+/// ```
+/// use rsef_rs::{coalesce_ipv4, into_records};
+/// use std::net::Ipv4Addr;
+///
+/// let listing = "ripencc|NL|ipv4|193.0.4.0|256|19930901|assigned|abc\n\
+///                ripencc|NL|ipv4|193.0.0.0|1024|19930901|assigned|abc\n\
+///                ripencc|NL|ipv4|193.0.8.0|256|19930901|assigned|abc\n";
+/// let records = into_records(rsef_rs::parse_str(listing).unwrap()).collect::<Vec<_>>();
+///
+/// assert_eq!(
+///     coalesce_ipv4(&records),
+///     vec![
+///         (Ipv4Addr::new(193, 0, 0, 0), Ipv4Addr::new(193, 0, 4, 255)),
+///         (Ipv4Addr::new(193, 0, 8, 0), Ipv4Addr::new(193, 0, 8, 255)),
+///     ]
+/// );
+/// ```
+///
+pub fn coalesce_ipv4(records: &[Record]) -> Vec<(Ipv4Addr, Ipv4Addr)> {
+    let mut ranges = records
+        .iter()
+        .filter_map(Record::ipv4_range)
+        .collect::<Vec<_>>();
+    ranges.sort_unstable();
+
+    let mut coalesced: Vec<(u32, u32)> = Vec::new();
+    for (start, end) in ranges {
+        match coalesced.last_mut() {
+            // Compare as u64, so a range that ends at 255.255.255.255 does not overflow.
+            Some(last) if u64::from(start) <= u64::from(last.1) + 1 => last.1 = last.1.max(end),
+            _ => coalesced.push((start, end)),
+        }
+    }
+
+    coalesced
+        .into_iter()
+        .map(|(start, end)| (Ipv4Addr::from(start), Ipv4Addr::from(end)))
+        .collect()
+}
+
+/// Returns the fields on which records are considered duplicates.
+fn dedup_key(record: &Record) -> (String, Type, String, u32, Status) {
+    (
+        record.registry.clone(),
+        record.res_type,
+        record.start.clone(),
+        record.value,
+        record.status.clone(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{coalesce_ipv4, dedup, into_records, merge, read_all, Line, Status};
+
+    use std::net::Ipv4Addr;
+
+    fn parse(listing: &str) -> Vec<Line> {
+        read_all(listing.as_bytes()).unwrap().collect()
+    }
+
+    #[test]
+    fn test_merge() {
+        let ripe = parse(
+            "2|ripencc|1|3|19830705|20190201|+0100\n\
+             ripencc|*|ipv4|*|2|summary\n\
+             ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+             ripencc|NL|ipv6|2001:67c:2e8::|48|20110111|assigned|abc\n\
+             ripencc|NL|asn|3333|1|19930901|assigned|abc\n",
+        );
+        let arin = parse(
+            "2|arin|1|3|19830705|20190201|-0500\n\
+             arin|US|ipv4|8.8.8.0|256|19921201|allocated|def\n\
+             arin|US|ipv4|24.0.0.0|256|19921201|allocated|def\n\
+             arin|US|asn|701|1|19900803|assigned|def\n",
+        );
+
+        let merged = merge(vec![ripe, arin]);
+        let starts = merged
+            .iter()
+            .map(|line| match line {
+                Line::Record(x) => x.start.as_str(),
+                _ => panic!("Expected a record line"),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            starts,
+            vec![
+                "701",
+                "3333",
+                "8.8.8.0",
+                "24.0.0.0",
+                "193.0.0.0",
+                "2001:67c:2e8::"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut lines = parse(
+            "2|ripencc|1|3|19830705|20190201|+0100\n\
+             ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+             ripencc|NL|asn|3333|1|19930901|assigned|abc\n\
+             ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|def\n\
+             ripencc|NL|ipv4|193.0.0.0|256|19930901|allocated|abc\n\
+             ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n",
+        );
+
+        dedup(&mut lines);
+        assert_eq!(lines.len(), 4);
+        assert!(matches!(lines[0], Line::Version(_)));
+
+        let records = lines
+            .iter()
+            .filter_map(|line| match line {
+                Line::Record(x) => Some((x.start.as_str(), x.status.clone(), x.id.as_str())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            records,
+            vec![
+                ("193.0.0.0", Status::Assigned, "abc"),
+                ("3333", Status::Assigned, "abc"),
+                ("193.0.0.0", Status::Allocated, "abc"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_coalesce_ipv4() {
+        let records = into_records(parse(
+            "ripencc|NL|ipv4|10.0.1.0|256|19930901|assigned|abc\n\
+             ripencc|NL|ipv4|10.0.0.0|256|19930901|assigned|abc\n\
+             ripencc|NL|ipv4|10.0.0.128|64|19930901|assigned|abc\n\
+             ripencc|NL|ipv4|10.0.3.0|256|19930901|assigned|abc\n\
+             ripencc|NL|ipv4|255.255.255.0|256|19930901|assigned|abc\n\
+             ripencc|NL|ipv4|255.255.254.0|256|19930901|assigned|abc\n\
+             ripencc|NL|ipv6|2001:67c:2e8::|48|20110111|assigned|abc\n\
+             ripencc|NL|ipv4|10.0.2.0|0|19930901|assigned|abc\n",
+        ))
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            coalesce_ipv4(&records),
+            vec![
+                (Ipv4Addr::new(10, 0, 0, 0), Ipv4Addr::new(10, 0, 1, 255)),
+                (Ipv4Addr::new(10, 0, 3, 0), Ipv4Addr::new(10, 0, 3, 255)),
+                (
+                    Ipv4Addr::new(255, 255, 254, 0),
+                    Ipv4Addr::new(255, 255, 255, 255)
+                ),
+            ]
+        );
+        assert!(coalesce_ipv4(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_coalesce_ipv4_across_organizations() {
+        let records = into_records(parse(
+            "ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc
+             arin|US|ipv4|193.0.1.0|256|19921201|allocated|def
+",
+        ))
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            coalesce_ipv4(&records),
+            vec![(Ipv4Addr::new(193, 0, 0, 0), Ipv4Addr::new(193, 0, 1, 255))]
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//!
+//! A builder to select the records of parsed RSEF listings by combining several filters.
+//!
+
+use crate::{records, Line, Record, Status, Type};
+
+/// Selects the records of a listing that match every filter that was added. Without any filters,
+/// all records are selected. Summary and version lines are never selected.
+///
+/// # Example
+/// ```
+/// use rsef_rs::{Query, Type};
+///
+/// let listing = "ripencc|DE|ipv4|193.0.0.0|256|19930901|allocated|abc\n\
+///                ripencc|DE|ipv4|193.0.4.0|256|20120101|assigned|abc\n\
+///                ripencc|NL|ipv4|193.0.8.0|256|19930901|allocated|def\n\
+///                ripencc|DE|asn|3320|1|19930901|allocated|ghi\n";
+/// let lines = rsef_rs::parse_str(listing).unwrap();
+///
+/// let records = Query::new(&lines)
+///     .res_type(Type::IPv4)
+///     .country("DE")
+///     .status("allocated")
+///     .collect();
+///
+/// assert_eq!(records.len(), 1);
+/// assert_eq!(records[0].start, "193.0.0.0");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Query<'a> {
+    lines: &'a [Line],
+    registry: Option<String>,
+    res_type: Option<Type>,
+    country: Option<String>,
+    status: Option<Status>,
+    since: Option<String>,
+    until: Option<String>,
+}
+
+impl<'a> Query<'a> {
+    /// Creates a query that selects all the records found in the given lines.
+    pub fn new(lines: &'a [Line]) -> Self {
+        Query {
+            lines,
+            registry: None,
+            res_type: None,
+            country: None,
+            status: None,
+            since: None,
+            until: None,
+        }
+    }
+
+    /// Only selects the records of the given registry, such as `ripencc`.
+    pub fn registry(mut self, registry: impl Into<String>) -> Self {
+        self.registry = Some(registry.into());
+        self
+    }
+
+    /// Only selects the records of the given resource type.
+    pub fn res_type(mut self, res_type: Type) -> Self {
+        self.res_type = Some(res_type);
+        self
+    }
+
+    /// Only selects the records assigned to the given ISO 3166 country code, ignoring case.
+    pub fn country(mut self, country: impl Into<String>) -> Self {
+        self.country = Some(country.into());
+        self
+    }
+
+    /// Only selects the records with the given status, ignoring case like `records_by_status`.
+    pub fn status(mut self, status: impl Into<Status>) -> Self {
+        self.status = Some(status.into());
+        self
+    }
+
+    /// Only selects the records allocated on or after the given date, in yyyymmdd format.
+    /// Records without a date are not selected.
+    pub fn since(mut self, date: impl Into<String>) -> Self {
+        self.since = Some(date.into());
+        self
+    }
+
+    /// Only selects the records allocated on or before the given date, in yyyymmdd format.
+    /// Records without a date are not selected.
+    pub fn until(mut self, date: impl Into<String>) -> Self {
+        self.until = Some(date.into());
+        self
+    }
+
+    /// Returns whether the record matches every filter of this query.
+    pub fn matches(&self, record: &Record) -> bool {
+        if let Some(registry) = &self.registry {
+            if record.registry != *registry {
+                return false;
+            }
+        }
+
+        if let Some(res_type) = self.res_type {
+            if record.res_type != res_type {
+                return false;
+            }
+        }
+
+        if let Some(country) = &self.country {
+            if !record.organization.eq_ignore_ascii_case(country) {
+                return false;
+            }
+        }
+
+        if let Some(status) = &self.status {
+            if !record.status.as_str().eq_ignore_ascii_case(status.as_str()) {
+                return false;
+            }
+        }
+
+        if let Some(since) = &self.since {
+            if record.date.is_empty() || record.date < *since {
+                return false;
+            }
+        }
+
+        if let Some(until) = &self.until {
+            if record.date.is_empty() || record.date > *until {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Returns an iterator over the records that match every filter of this query.
+    pub fn iter(&self) -> impl Iterator<Item = &'a Record> + '_ {
+        records(self.lines).filter(move |record| self.matches(record))
+    }
+
+    /// Returns the records that match every filter of this query, in the order they were found.
+    pub fn collect(&self) -> Vec<&'a Record> {
+        self.iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_str, Query, Record, Type};
+
+    #[test]
+    fn test_query() {
+        let lines = parse_str(
+            "2|ripencc|1|4|19830705|20190201|+0100\n\
+             ripencc|*|ipv4|*|3|summary\n\
+             ripencc|DE|ipv4|193.0.0.0|256|19930901|allocated|a\n\
+             ripencc|de|ipv4|193.0.4.0|256|20120101|ALLOCATED|b\n\
+             ripencc|ZZ|ipv4|193.0.8.0|256||available|\n\
+             arin|US|asn|701|1|19900803|assigned|c\n",
+        )
+        .unwrap();
+
+        let ids = |query: Query| {
+            query
+                .collect()
+                .into_iter()
+                .map(|record: &Record| record.id.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids(Query::new(&lines)), vec!["a", "b", "", "c"]);
+        assert_eq!(ids(Query::new(&lines).country("DE")), vec!["a", "b"]);
+        assert_eq!(ids(Query::new(&lines).status("allocated")), vec!["a", "b"]);
+        assert_eq!(ids(Query::new(&lines).res_type(Type::ASN)), vec!["c"]);
+        assert_eq!(ids(Query::new(&lines).registry("ripencc")).len(), 3);
+        assert_eq!(ids(Query::new(&lines).since("20000101")), vec!["b"]);
+        assert_eq!(ids(Query::new(&lines).until("19991231")), vec!["a", "c"]);
+        assert_eq!(
+            ids(Query::new(&lines)
+                .res_type(Type::IPv4)
+                .since("19930901")
+                .until("19930901")),
+            vec!["a"]
+        );
+    }
+}
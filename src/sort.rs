@@ -0,0 +1,91 @@
+//!
+//! Functionality to sort the records of RSEF listings in a deterministic order.
+//!
+
+use crate::{Record, Type};
+
+///
+/// Sorts records by resource type, ASN records first, then IPv4 and then IPv6 records. Records of
+/// the same type are sorted numerically by the AS number or address they start at, so `8.8.8.0`
+/// comes before `24.0.0.0`. Records whose start cannot be parsed are placed after the other records
+/// of their type.
+///
+/// The sort is stable: records that start at the same resource keep their order.
+///
+/// # Example
+/// ```
+/// use rsef_rs::{sort_records, Line};
+///
+/// let listing = "arin|US|ipv4|24.0.0.0|256|19921201|allocated|abc\n\
+///                arin|US|asn|701|1|19900803|assigned|abc\n\
+///                arin|US|ipv4|8.8.8.0|256|19921201|allocated|abc\n";
+///
+/// let mut records = rsef_rs::read_all(listing.as_bytes())
+///     .unwrap()
+///     .filter_map(|line| match line {
+///         Line::Record(record) => Some(record),
+///         _ => None,
+///     })
+///     .collect::<Vec<_>>();
+///
+/// sort_records(&mut records);
+/// assert_eq!(records[1].start, "8.8.8.0");
+/// ```
+///
+pub fn sort_records(records: &mut [Record]) {
+    records.sort_by_key(sort_key);
+}
+
+/// Returns the key on which records are sorted: the rank of their resource type, followed by
+/// their start as an integer, if it can be parsed.
+fn sort_key(record: &Record) -> (u8, bool, u128) {
+    let (rank, start) = match record.res_type {
+        Type::ASN => (0, record.start.parse::<u32>().ok().map(u128::from)),
+        Type::IPv4 => (1, record.start_ipv4().map(|x| u128::from(u32::from(x)))),
+        Type::IPv6 => (2, record.start_ipv6().map(u128::from)),
+        Type::Unknown => (3, None),
+    };
+
+    (rank, start.is_none(), start.unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{sort_records, Record, Status, Type};
+
+    fn record(res_type: Type, start: &str, id: &str) -> Record {
+        Record {
+            registry: "ripencc".to_string(),
+            organization: "NL".to_string(),
+            res_type,
+            start: start.to_string(),
+            value: 1,
+            date: "19930901".to_string(),
+            status: Status::Assigned,
+            id: id.to_string(),
+            flags: None,
+            extra: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_sort_records() {
+        let mut records = vec![
+            record(Type::IPv6, "2001:67c:2e8::", "a"),
+            record(Type::IPv6, "2001:db8::", "b"),
+            record(Type::IPv4, "193.0.0.0", "c"),
+            record(Type::ASN, "3333", "d"),
+            record(Type::IPv4, "invalid", "e"),
+            record(Type::IPv4, "24.0.0.0", "f"),
+            record(Type::ASN, "701", "g"),
+            record(Type::IPv4, "8.8.8.0", "h"),
+            record(Type::IPv6, "2001:67c:2e8::", "i"),
+            record(Type::ASN, "65536", "j"),
+        ];
+
+        sort_records(&mut records);
+
+        let ids = records.iter().map(|x| x.id.as_str()).collect::<String>();
+        assert_eq!(ids, "gdjhfceaib");
+    }
+}
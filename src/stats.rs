@@ -0,0 +1,291 @@
+//!
+//! Functionality to compute aggregate statistics over parsed RSEF listings.
+//!
+
+use crate::{records, records_of_type, Line, LineReader, RsefError, Status, Type};
+
+use std::collections::HashMap;
+use std::io::Read;
+
+/// Aggregate statistics on the Internet resources assigned or allocated to a single country.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CountryStats {
+    /// The number of ASN records.
+    pub asn_records: usize,
+
+    /// The total number of AS numbers in the ASN records.
+    pub asns: u64,
+
+    /// The number of IPv4 records.
+    pub ipv4_records: usize,
+
+    /// The total number of IPv4 addresses in the IPv4 records.
+    pub ipv4_addresses: u64,
+
+    /// The number of IPv6 records.
+    pub ipv6_records: usize,
+
+    /// The total number of IPv6 addresses in the IPv6 records, saturating at u128::MAX.
+    pub ipv6_addresses: u128,
+}
+
+///
+/// Computes the statistics of every country found in the records of a listing, keyed by the
+/// ISO 3166 2-letter country code. Records without a country code are counted under `ZZ`, the
+/// code the registries themselves use for resources that are not assigned to any country.
+///
+pub fn stats_by_country(lines: &[Line]) -> HashMap<String, CountryStats> {
+    let mut stats: HashMap<String, CountryStats> = HashMap::new();
+
+    for record in records(lines) {
+        let country = if record.organization.is_empty() {
+            "ZZ"
+        } else {
+            &record.organization
+        };
+
+        let entry = stats.entry(country.to_string()).or_default();
+        match record.res_type {
+            Type::ASN => {
+                entry.asn_records += 1;
+                entry.asns += record.value_u64();
+            }
+            Type::IPv4 => {
+                entry.ipv4_records += 1;
+                entry.ipv4_addresses += record.value_u64();
+            }
+            Type::IPv6 => {
+                entry.ipv6_records += 1;
+                entry.ipv6_addresses = entry
+                    .ipv6_addresses
+                    .saturating_add(ipv6_addresses(record.value));
+            }
+            Type::Unknown => {}
+        }
+    }
+
+    stats
+}
+
+///
+/// Returns the total number of IPv4 addresses in the IPv4 records found in the given lines.
+///
+pub fn total_ipv4_addresses(lines: &[Line]) -> u64 {
+    records_of_type(lines, Type::IPv4)
+        .map(|record| record.value_u64())
+        .sum()
+}
+
+///
+/// Counts the records found in the given lines per status. Statuses other than the ones defined
+/// by the format are counted under their own `Status::Other` key.
+///
+pub fn count_by_status(lines: &[Line]) -> HashMap<Status, usize> {
+    let mut counts = HashMap::new();
+
+    for record in records(lines) {
+        *counts.entry(record.status.clone()).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+///
+/// Returns the total number of AS numbers in the ASN records found in the given lines.
+///
+pub fn total_asns(lines: &[Line]) -> u64 {
+    records_of_type(lines, Type::ASN)
+        .map(|record| record.value_u64())
+        .sum()
+}
+
+///
+/// Returns the number of CIDR blocks that the IPv4 records found in the given lines decompose
+/// into, see `Record::ipv4_cidrs`. Records that do not describe a valid range are not counted.
+///
+/// Requires the `ipnet` feature.
+///
+#[cfg(feature = "ipnet")]
+pub fn ipv4_prefix_count(lines: &[Line]) -> usize {
+    records_of_type(lines, Type::IPv4)
+        .map(|record| record.ipv4_cidrs().len())
+        .sum()
+}
+
+/// The totals of the Internet resources found in the records of a listing.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ResourceTotals {
+    /// The number of ASN records.
+    pub asn_count: usize,
+
+    /// The total number of IPv4 addresses in the IPv4 records.
+    pub ipv4_addresses: u64,
+
+    /// The number of IPv6 records, each of which describes a single prefix.
+    pub ipv6_prefixes: usize,
+}
+
+///
+/// Computes the totals of the Internet resources found in a stream. Every line is parsed and
+/// counted as it is read, so unlike `read_all` the listing is never held in memory as a whole.
+///
+/// Returns an error as soon as the stream cannot be read or a malformed line is encountered.
+///
+pub fn summarize<R: Read>(read: R) -> Result<ResourceTotals, RsefError> {
+    let mut totals = ResourceTotals::default();
+
+    for line in LineReader::new(read) {
+        if let Line::Record(record) = line? {
+            match record.res_type {
+                Type::ASN => totals.asn_count += 1,
+                Type::IPv4 => totals.ipv4_addresses += record.value_u64(),
+                Type::IPv6 => totals.ipv6_prefixes += 1,
+                Type::Unknown => {}
+            }
+        }
+    }
+
+    Ok(totals)
+}
+
+/// Returns the number of addresses in an IPv6 prefix of the given length.
+fn ipv6_addresses(prefix: u32) -> u128 {
+    match prefix {
+        0 => u128::MAX,
+        1..=128 => 1 << (128 - prefix),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        count_by_status, read_all, stats_by_country, summarize, total_asns, total_ipv4_addresses,
+        Line, ResourceTotals, Status,
+    };
+
+    #[test]
+    fn test_stats_by_country() {
+        let lines = read_all(
+            "ripencc|DE|ipv4|193.0.0.0|256|19930901|allocated|abc\n\
+             ripencc|DE|ipv4|193.0.4.0|1024|19930901|allocated|abc\n\
+             ripencc|DE|ipv6|2001:67c::|32|19930901|allocated|abc\n\
+             ripencc|DE|asn|3320|2|19930901|assigned|abc\n\
+             ripencc||ipv4|193.0.8.0|256|19930901|available|\n\
+             ripencc|ZZ|asn|3333|1|19930901|reserved|\n"
+                .as_bytes(),
+        )
+        .unwrap()
+        .collect::<Vec<Line>>();
+
+        let stats = stats_by_country(&lines);
+        assert_eq!(stats.len(), 2);
+
+        let de = &stats["DE"];
+        assert_eq!(de.ipv4_records, 2);
+        assert_eq!(de.ipv4_addresses, 1280);
+        assert_eq!(de.ipv6_records, 1);
+        assert_eq!(de.ipv6_addresses, 1 << 96);
+        assert_eq!(de.asn_records, 1);
+        assert_eq!(de.asns, 2);
+
+        let zz = &stats["ZZ"];
+        assert_eq!(zz.ipv4_addresses, 256);
+        assert_eq!(zz.asns, 1);
+    }
+
+    #[test]
+    fn test_count_by_status() {
+        let lines = read_all(
+            "2|ripencc|1|6|19830705|20190201|+0100\n\
+             ripencc|*|ipv4|*|5|summary\n\
+             ripencc|DE|ipv4|193.0.0.0|256|19930901|allocated|abc\n\
+             ripencc|DE|ipv4|193.0.4.0|256|19930901|ALLOCATED|abc\n\
+             ripencc|DE|ipv4|193.0.8.0|256|19930901|assigned|abc\n\
+             ripencc|ZZ|ipv4|193.0.12.0|256||reserved|\n\
+             ripencc|ZZ|ipv4|193.0.16.0|256||available|\n\
+             ripencc|DE|asn|3320|1|19930901|legacy|abc\n"
+                .as_bytes(),
+        )
+        .unwrap()
+        .collect::<Vec<Line>>();
+
+        let counts = count_by_status(&lines);
+        assert_eq!(counts.len(), 5);
+        assert_eq!(counts[&Status::Allocated], 2);
+        assert_eq!(counts[&Status::Assigned], 1);
+        assert_eq!(counts[&Status::Reserved], 1);
+        assert_eq!(counts[&Status::Available], 1);
+        assert_eq!(counts[&Status::Other("legacy".to_string())], 1);
+    }
+
+    #[test]
+    fn test_totals() {
+        let lines = read_all(
+            "arin|US|ipv4|3.0.0.0|16777216|19880223|allocated|abc\n\
+             arin|US|ipv4|4.0.0.0|16777216|19921201|allocated|abc\n\
+             arin|US|ipv4|8.8.8.0|256|19921201|allocated|abc\n\
+             arin|US|ipv6|2001:4860::|32|20050314|allocated|abc\n\
+             arin|US|asn|701|5|19900803|assigned|abc\n\
+             arin|US|asn|15169|1|20000330|assigned|abc\n"
+                .as_bytes(),
+        )
+        .unwrap()
+        .collect::<Vec<Line>>();
+
+        assert_eq!(total_ipv4_addresses(&lines), 2 * 16_777_216 + 256);
+        assert_eq!(total_asns(&lines), 6);
+        assert_eq!(total_ipv4_addresses(&[]), 0);
+    }
+
+    #[test]
+    fn test_totals_exceed_u32() {
+        let lines = read_all(
+            "iana|ZZ|ipv4|0.0.0.0|2147483648||reserved|\n\
+             iana|ZZ|ipv4|128.0.0.0|2147483648||reserved|\n\
+             iana|ZZ|ipv4|10.0.0.0|16777216||reserved|\n"
+                .as_bytes(),
+        )
+        .unwrap()
+        .collect::<Vec<Line>>();
+
+        assert_eq!(total_ipv4_addresses(&lines), (1 << 32) + 16_777_216);
+    }
+
+    #[test]
+    #[cfg(feature = "ipnet")]
+    fn test_ipv4_prefix_count() {
+        let lines = read_all(
+            "ripencc|NL|ipv4|192.0.3.0|1536|19930901|assigned|abc\n\
+             ripencc|NL|asn|3333|1|19930901|assigned|abc\n"
+                .as_bytes(),
+        )
+        .unwrap()
+        .collect::<Vec<Line>>();
+
+        // 192.0.3.0/24, 192.0.4.0/22 and 192.0.8.0/24.
+        assert_eq!(crate::ipv4_prefix_count(&lines), 3);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let listing = "2|ripencc|1|5|19830705|20190201|+0100\n\
+                       ripencc|*|ipv4|*|2|summary\n\
+                       ripencc|DE|ipv4|193.0.0.0|256|19930901|allocated|abc\n\
+                       ripencc|DE|ipv4|193.0.4.0|1024|19930901|allocated|abc\n\
+                       ripencc|DE|ipv6|2001:67c::|32|19930901|allocated|abc\n\
+                       ripencc|DE|asn|3320|2|19930901|assigned|abc\n\
+                       ripencc|ZZ|asn|3333|1|19930901|reserved|\n";
+
+        assert_eq!(
+            summarize(listing.as_bytes()).unwrap(),
+            ResourceTotals {
+                asn_count: 2,
+                ipv4_addresses: 1280,
+                ipv6_prefixes: 1,
+            }
+        );
+
+        assert!(summarize("ripencc|DE|ipv4\n".as_bytes()).is_err());
+    }
+}
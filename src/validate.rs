@@ -0,0 +1,233 @@
+//!
+//! Functionality to check the integrity of parsed RSEF listings.
+//!
+
+use crate::{records, records_of_type, Line, Record, Type, ValidationError};
+
+use std::collections::HashMap;
+
+///
+/// Checks that the number of records matches the count declared in the version line, and that the
+/// number of records of every resource type matches the count in its summary line. This catches
+/// truncated listings that otherwise parse successfully.
+///
+pub fn validate(lines: &[Line]) -> Result<(), ValidationError> {
+    let (expected, actual) = record_integrity(lines).ok_or(ValidationError::MissingVersion)?;
+    if actual != expected as usize {
+        return Err(ValidationError::RecordCount { expected, actual });
+    }
+
+    for line in lines {
+        if let Line::Summary(summary) = line {
+            let actual = records_of_type(lines, summary.res_type).count();
+            if actual != summary.count as usize {
+                return Err(ValidationError::SummaryCount {
+                    res_type: summary.res_type,
+                    expected: summary.count,
+                    actual,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+///
+/// Returns the number of records declared in the first version line along with the number of
+/// records actually found in the given lines, or None if there is no version line.
+///
+pub fn record_integrity(lines: &[Line]) -> Option<(u32, usize)> {
+    let declared = lines.iter().find_map(|line| match line {
+        Line::Version(version) => Some(version.records),
+        _ => None,
+    })?;
+
+    Some((declared, records(lines).count()))
+}
+
+/// Describes a summary line whose count differs from the number of records it summarizes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SummaryMismatch {
+    /// The registry of the summary line.
+    pub registry: String,
+
+    /// The resource type of the summary line.
+    pub res_type: Type,
+
+    /// The number of records declared in the summary line.
+    pub expected: u32,
+
+    /// The number of records of this registry and resource type found in the listing.
+    pub actual: usize,
+}
+
+impl SummaryMismatch {
+    /// Returns how many more records were found than declared, which is negative if records are
+    /// missing.
+    pub fn difference(&self) -> i64 {
+        self.actual as i64 - i64::from(self.expected)
+    }
+}
+
+///
+/// Compares the count of every summary line with the number of records of its registry and
+/// resource type, and returns the summary lines whose count does not match. Unlike `validate`,
+/// records are only counted for the registry of the summary line, so listings that combine the
+/// listings of several registries are checked correctly as well.
+///
+pub fn check_summaries(lines: &[Line]) -> Vec<SummaryMismatch> {
+    let mut mismatches = Vec::new();
+
+    for line in lines {
+        if let Line::Summary(summary) = line {
+            let actual = records_of_type(lines, summary.res_type)
+                .filter(|record| record.registry == summary.registry)
+                .count();
+
+            if actual != summary.count as usize {
+                mismatches.push(SummaryMismatch {
+                    registry: summary.registry.clone(),
+                    res_type: summary.res_type,
+                    expected: summary.count,
+                    actual,
+                });
+            }
+        }
+    }
+
+    mismatches
+}
+
+///
+/// Finds the records that share their resource type and start with an earlier record, which a
+/// well-formed listing never contains. Unlike `dedup`, the records are reported even if their other
+/// fields differ, as that indicates conflicting records rather than repeated ones.
+///
+/// Every duplicate is returned along with the first record with the same resource type and start.
+///
+pub fn find_duplicate_starts(lines: &[Line]) -> Vec<(&Record, &Record)> {
+    let mut first = HashMap::new();
+    let mut duplicates = Vec::new();
+
+    for record in records(lines) {
+        match first.get(&(record.res_type, record.start.as_str())) {
+            Some(original) => duplicates.push((*original, record)),
+            None => {
+                first.insert((record.res_type, record.start.as_str()), record);
+            }
+        }
+    }
+
+    duplicates
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        check_summaries, find_duplicate_starts, read_all, record_integrity, validate, Line,
+        SummaryMismatch, Type, ValidationError,
+    };
+
+    fn parse(listing: &str) -> Vec<Line> {
+        read_all(listing.as_bytes()).unwrap().collect()
+    }
+
+    #[test]
+    fn test_validate() {
+        let lines = parse(
+            "2|ripencc|1|2|19830705|20190201|+0100\n\
+             ripencc|*|ipv4|*|1|summary\n\
+             ripencc|*|asn|*|1|summary\n\
+             ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+             ripencc|NL|asn|3333|1|19930901|assigned|abc\n",
+        );
+        assert_eq!(validate(&lines), Ok(()));
+
+        let lines = parse(
+            "2|ripencc|1|3|19830705|20190201|+0100\n\
+             ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n",
+        );
+        assert_eq!(
+            validate(&lines),
+            Err(ValidationError::RecordCount {
+                expected: 3,
+                actual: 1
+            })
+        );
+
+        let lines = parse(
+            "2|ripencc|1|1|19830705|20190201|+0100\n\
+             ripencc|*|ipv4|*|2|summary\n\
+             ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n",
+        );
+        assert_eq!(
+            validate(&lines),
+            Err(ValidationError::SummaryCount {
+                res_type: Type::IPv4,
+                expected: 2,
+                actual: 1
+            })
+        );
+
+        let lines = parse("ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n");
+        assert_eq!(validate(&lines), Err(ValidationError::MissingVersion));
+    }
+
+    #[test]
+    fn test_check_summaries() {
+        let lines = parse(
+            "2|ripencc|1|3|19830705|20190201|+0100\n\
+             ripencc|*|ipv4|*|3|summary\n\
+             ripencc|*|asn|*|1|summary\n\
+             ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+             ripencc|NL|asn|3333|1|19930901|assigned|abc\n\
+             2|arin|1|1|19830705|20190201|-0500\n\
+             arin|*|ipv4|*|1|summary\n\
+             arin|US|ipv4|8.8.8.0|256|19921201|allocated|def\n",
+        );
+
+        let mismatches = check_summaries(&lines);
+        assert_eq!(
+            mismatches,
+            vec![SummaryMismatch {
+                registry: "ripencc".to_string(),
+                res_type: Type::IPv4,
+                expected: 3,
+                actual: 1,
+            }]
+        );
+        assert_eq!(mismatches[0].difference(), -2);
+    }
+
+    #[test]
+    fn test_record_integrity() {
+        let lines = parse(
+            "2|ripencc|1|3|19830705|20190201|+0100\n\
+             ripencc|*|ipv4|*|2|summary\n\
+             ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+             ripencc|NL|ipv4|193.0.4.0|256|19930901|assigned|abc\n",
+        );
+        assert_eq!(record_integrity(&lines), Some((3, 2)));
+
+        let lines = parse("ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n");
+        assert_eq!(record_integrity(&lines), None);
+    }
+
+    #[test]
+    fn test_find_duplicate_starts() {
+        let lines = parse(
+            "ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+             ripencc|NL|asn|193|1|19930901|assigned|abc\n\
+             ripencc|DE|ipv4|193.0.0.0|512|20010101|allocated|def\n\
+             ripencc|NL|ipv4|193.0.4.0|256|19930901|assigned|abc\n",
+        );
+
+        let duplicates = find_duplicate_starts(&lines);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].0.organization, "NL");
+        assert_eq!(duplicates[0].1.organization, "DE");
+
+        assert!(find_duplicate_starts(&lines[..2]).is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+//!
+//! Functionality to serialize parsed RSEF entries back into the `|`-delimited RSEF format.
+//!
+
+use crate::{Line, Record, Summary, Version};
+
+use std::fmt;
+use std::io::Write;
+
+/// Formats a Version as an RSEF version line.
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}|{}|{}|{}|{}|{}|{}",
+            self.version,
+            self.registry,
+            self.serial,
+            self.records,
+            self.start_date,
+            self.end_date,
+            self.utc_offset
+        )
+    }
+}
+
+/// Formats a Summary as an RSEF summary line.
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}|*|{}|*|{}|summary",
+            self.registry, self.res_type, self.count
+        )
+    }
+}
+
+/// Formats a Record as an RSEF record line. The id is only included when it is non-empty or when
+/// the record has flags or extra fields, which are written after it.
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}|{}|{}|{}|{}|{}|{}",
+            self.registry,
+            self.organization,
+            self.res_type,
+            self.start,
+            self.value,
+            self.date,
+            self.status
+        )?;
+
+        if !self.id.is_empty() || self.flags.is_some() || !self.extra.is_empty() {
+            write!(f, "|{}", self.id)?;
+        }
+
+        if self.flags.is_some() || !self.extra.is_empty() {
+            write!(f, "|{}", self.flags.as_deref().unwrap_or(""))?;
+        }
+
+        for field in &self.extra {
+            write!(f, "|{}", field)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Formats a Line as the RSEF line it represents.
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Line::Version(x) => x.fmt(f),
+            Line::Summary(x) => x.fmt(f),
+            Line::Record(x) => x.fmt(f),
+        }
+    }
+}
+
+///
+/// Writes the given RSEF entries to a stream in the RSEF format, one line per entry.
+///
+pub fn write_all<W: Write>(lines: &[Line], mut out: W) -> std::io::Result<()> {
+    for line in lines {
+        writeln!(out, "{}", line)?;
+    }
+
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{read_all, write_all, Line};
+
+    #[test]
+    fn test_round_trip() {
+        let listing = "2.3|ripencc|20190201|2|19830705|20190201|+0100\n\
+                       ripencc|*|ipv4|*|1|summary\n\
+                       ripencc|*|ipv6|*|1|summary\n\
+                       ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n\
+                       ripencc|ZZ|ipv6|2001:67c:2e8::|48|19930901|reserved\n\
+                       ripencc|NL|ipv6|2001:67c::|32|19930901|assigned||e-stats\n";
+
+        let lines = read_all(listing.as_bytes()).unwrap().collect::<Vec<Line>>();
+
+        let mut output = Vec::new();
+        write_all(&lines, &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), listing);
+    }
+
+    #[test]
+    fn test_display_line() {
+        let listing = "2.3|ripencc|20190201|1|19830705|20190201|+0100\n\
+                       ripencc|*|ipv4|*|1|summary\n\
+                       ripencc|NL|ipv4|193.0.0.0|256|19930901|assigned|abc\n";
+
+        for (line, text) in read_all(listing.as_bytes()).unwrap().zip(listing.lines()) {
+            assert_eq!(line.to_string(), text);
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use rsef_rs::{validate, Line, RsefError, Type, ValidationError};
+
+const FIXTURE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/data/delegated-ripencc-extended-20190201"
+);
+
+const SLICED_FIXTURE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/data/delegated-arin-sliced"
+);
+
+#[cfg(feature = "libflate")]
+const GZIP_FIXTURE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/data/delegated-apnic-extended-20190201.gz"
+);
+
+#[cfg(feature = "zstd")]
+const ZSTD_FIXTURE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/data/delegated-ripencc-extended-20190201.zst"
+);
+
+#[test]
+fn read_file() {
+    let lines = rsef_rs::read_file(FIXTURE).unwrap();
+    assert_eq!(lines.len(), 10);
+
+    match &lines[0] {
+        Line::Version(x) => assert_eq!(x.serial, "20190201"),
+        _ => panic!("Expected a version line"),
+    }
+
+    assert_eq!(rsef_rs::records_of_type(&lines, Type::IPv4).count(), 3);
+    assert_eq!(validate(&lines), Ok(()));
+}
+
+#[test]
+#[cfg(feature = "libflate")]
+fn read_gzip_file() {
+    let lines = rsef_rs::read_file(GZIP_FIXTURE).unwrap();
+    assert_eq!(lines.len(), 9);
+
+    match &lines[0] {
+        Line::Version(x) => assert_eq!(x.registry, "apnic"),
+        _ => panic!("Expected a version line"),
+    }
+
+    assert_eq!(rsef_rs::records_of_type(&lines, Type::IPv6).count(), 2);
+    assert_eq!(validate(&lines), Ok(()));
+}
+
+#[test]
+#[cfg(feature = "zstd")]
+fn read_zstd_file() {
+    let lines = rsef_rs::read_file(ZSTD_FIXTURE).unwrap();
+    assert_eq!(lines, rsef_rs::read_file(FIXTURE).unwrap());
+}
+
+#[test]
+fn read_file_without_version() {
+    let lines = rsef_rs::read_file(SLICED_FIXTURE).unwrap();
+    assert_eq!(lines.len(), 6);
+
+    assert!(matches!(lines[0], Line::Summary(_)));
+    assert_eq!(rsef_rs::records_of_type(&lines, Type::ASN).count(), 2);
+    assert_eq!(rsef_rs::records_of_type(&lines, Type::IPv4).count(), 2);
+    assert_eq!(validate(&lines), Err(ValidationError::MissingVersion));
+
+    let file = std::fs::File::open(SLICED_FIXTURE).unwrap();
+    assert!(matches!(
+        rsef_rs::read_all_with_header(file),
+        Err(RsefError::MissingVersion)
+    ));
+}
+
+#[test]
+fn read_missing_file() {
+    assert!(rsef_rs::read_file("tests/data/does-not-exist").is_err());
+}
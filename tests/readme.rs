@@ -1,10 +1,12 @@
-#[test]
-fn readme() {
+#![cfg(feature = "download")]
+
+#[tokio::test]
+async fn readme() {
     use rsef_rs::{Line, Registry};
 
     // Friday 1 February 2019 21:22:48
     let timestamp = 1_549_056_168;
-    let stream = Registry::RIPE.download(timestamp).unwrap();
+    let stream = Registry::RIPE.download(timestamp).await.unwrap();
     let records = rsef_rs::read_all(stream).unwrap();
 
     for x in records {